@@ -1,8 +1,14 @@
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 use aws_sdk_eks::types::Cluster;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use secrecy::SecretString;
 use serde::{Deserialize, Serialize};
 
-use crate::{eks, finding::Findings, k8s, version};
+use crate::{
+  eks,
+  finding::{self, Findings},
+  k8s, output, version,
+};
 
 /// Container of all findings collected
 #[derive(Debug, Serialize, Deserialize)]
@@ -14,17 +20,19 @@ pub(crate) struct Results {
   pub(crate) kubernetes: k8s::KubernetesFindings,
 }
 
-impl Results {
-  /// Returns true if there are no findings
-  pub(crate) fn to_stdout_table(&self) -> Result<String> {
+impl output::Output for Results {
+  fn to_stdout_table(&self) -> Result<String> {
     let mut output = String::new();
 
     // Ordered sub-group (AWS -> EKS -> K8s) and check number
     output.push_str(&self.subnets.pod_ips.to_stdout_table()?);
     output.push_str(&self.subnets.control_plane_ips.to_stdout_table()?);
     output.push_str(&self.cluster.cluster_health.to_stdout_table()?);
+    output.push_str(&self.kubernetes.api_services.to_stdout_table()?);
+    output.push_str(&self.cluster.end_of_support.to_stdout_table()?);
 
     output.push_str(&self.data_plane.eks_managed_nodegroup_health.to_stdout_table()?);
+    output.push_str(&self.kubernetes.node_health.to_stdout_table()?);
     output.push_str(&self.addons.health.to_stdout_table()?);
     output.push_str(&self.addons.version_compatibility.to_stdout_table()?);
 
@@ -34,44 +42,145 @@ impl Results {
     output.push_str(&self.kubernetes.version_skew.to_stdout_table()?);
     output.push_str(&self.kubernetes.min_replicas.to_stdout_table()?);
     output.push_str(&self.kubernetes.min_ready_seconds.to_stdout_table()?);
+    output.push_str(&self.kubernetes.pod_disruption_budget.to_stdout_table()?);
     output.push_str(&self.kubernetes.pod_topology_distribution.to_stdout_table()?);
-    output.push_str(&self.kubernetes.readiness_probe.to_stdout_table()?);
+    output.push_str(&self.kubernetes.pod_placement.to_stdout_table()?);
+    output.push_str(&self.kubernetes.bare_pods.to_stdout_table()?);
+    output.push_str(&self.kubernetes.eviction_blockers.to_stdout_table()?);
+    output.push_str(&self.kubernetes.probes.to_stdout_table()?);
     output.push_str(&self.kubernetes.termination_grace_period.to_stdout_table()?);
     output.push_str(&self.kubernetes.docker_socket.to_stdout_table()?);
     output.push_str(&self.kubernetes.pod_security_policy.to_stdout_table()?);
+    output.push_str(&self.kubernetes.admission_webhooks.to_stdout_table()?);
+    output.push_str(&self.kubernetes.ebs_csi_migration.to_stdout_table()?);
     output.push_str(&self.kubernetes.kube_proxy_version_skew.to_stdout_table()?);
+    output.push_str(&self.kubernetes.removed_apis.to_stdout_table()?);
+    output.push_str(&self.kubernetes.helm_releases.to_stdout_table()?);
+    output.push_str(&self.kubernetes.custom_resources.to_stdout_table()?);
+    output.push_str(&self.kubernetes.blocking_pod_disruption_budgets.to_stdout_table()?);
+    output.push_str(&self.kubernetes.gp2_volumes.to_stdout_table()?);
 
     Ok(output)
   }
 }
 
-/// Analyze the cluster provided to collect all reported findings
-pub(crate) async fn analyze(aws_shared_config: &aws_config::SdkConfig, cluster: &Cluster) -> Result<Results> {
-  // Construct clients once
-  let asg_client = aws_sdk_autoscaling::Client::new(aws_shared_config);
-  let ec2_client = aws_sdk_ec2::Client::new(aws_shared_config);
-  let eks_client = aws_sdk_eks::Client::new(aws_shared_config);
+impl Results {
+  /// Returns all of the findings flattened into their common representation
+  pub(crate) fn entries(&self) -> Vec<finding::Entry> {
+    let mut entries = Vec::new();
 
-  let cluster_name = cluster.name().unwrap();
+    entries.extend(finding::to_entries(&self.subnets.pod_ips));
+    entries.extend(finding::to_entries(&self.subnets.control_plane_ips));
+    entries.extend(finding::to_entries(&self.cluster.cluster_health));
+    entries.extend(finding::to_entries(&self.kubernetes.api_services));
+    entries.extend(finding::to_entries(&self.cluster.end_of_support));
 
-  let k8s_client = match kube::Client::try_default().await {
-    Ok(client) => client,
+    entries.extend(finding::to_entries(&self.data_plane.eks_managed_nodegroup_health));
+    entries.extend(finding::to_entries(&self.kubernetes.node_health));
+    entries.extend(finding::to_entries(&self.addons.health));
+    entries.extend(finding::to_entries(&self.addons.version_compatibility));
+
+    entries.extend(finding::to_entries(&self.data_plane.eks_managed_nodegroup_update));
+    entries.extend(finding::to_entries(&self.data_plane.self_managed_nodegroup_update));
+
+    entries.extend(finding::to_entries(&self.kubernetes.version_skew));
+    entries.extend(finding::to_entries(&self.kubernetes.min_replicas));
+    entries.extend(finding::to_entries(&self.kubernetes.min_ready_seconds));
+    entries.extend(finding::to_entries(&self.kubernetes.pod_disruption_budget));
+    entries.extend(finding::to_entries(&self.kubernetes.pod_topology_distribution));
+    entries.extend(finding::to_entries(&self.kubernetes.pod_placement));
+    entries.extend(finding::to_entries(&self.kubernetes.bare_pods));
+    entries.extend(finding::to_entries(&self.kubernetes.eviction_blockers));
+    entries.extend(finding::to_entries(&self.kubernetes.probes));
+    entries.extend(finding::to_entries(&self.kubernetes.termination_grace_period));
+    entries.extend(finding::to_entries(&self.kubernetes.docker_socket));
+    entries.extend(finding::to_entries(&self.kubernetes.pod_security_policy));
+    entries.extend(finding::to_entries(&self.kubernetes.admission_webhooks));
+    entries.extend(finding::to_entries(&self.kubernetes.ebs_csi_migration));
+    entries.extend(finding::to_entries(&self.kubernetes.kube_proxy_version_skew));
+    entries.extend(finding::to_entries(&self.kubernetes.removed_apis));
+    entries.extend(finding::to_entries(&self.kubernetes.helm_releases));
+    entries.extend(finding::to_entries(&self.kubernetes.custom_resources));
+    entries.extend(finding::to_entries(&self.kubernetes.blocking_pod_disruption_budgets));
+    entries.extend(finding::to_entries(&self.kubernetes.gp2_volumes));
+
+    entries
+  }
+}
+
+/// Create the client used to interact with the Kubernetes API of the cluster
+pub(crate) async fn get_k8s_client(cluster_name: &str) -> Result<kube::Client> {
+  match kube::Client::try_default().await {
+    Ok(client) => Ok(client),
     Err(_) => {
       bail!(
         "Unable to connect to cluster. Ensure kubeconfig file is present and updated to connect to the cluster.
       Try: aws eks update-kubeconfig --name {cluster_name}"
       );
     }
-  };
+  }
+}
+
+/// Create the client used to interact with the Kubernetes API of the cluster from the cluster details
+///
+/// Unlike `get_k8s_client`, this does not rely on a kubeconfig file and instead authenticates using
+/// the AWS credentials provided. This allows analyzing any number of clusters in a single invocation
+pub(crate) async fn get_eks_k8s_client(
+  aws_shared_config: &aws_config::SdkConfig,
+  cluster: &Cluster,
+) -> Result<kube::Client> {
+  let cluster_name = cluster.name().context("Cluster name not found")?;
+  let endpoint = cluster.endpoint().context("Cluster endpoint not found")?;
+  let ca_data = cluster
+    .certificate_authority()
+    .and_then(|ca| ca.data())
+    .context("Cluster certificate authority not found")?;
+
+  // The certificate authority data is a base64 encoded PEM, the client expects DER encoded certificates
+  let pem = String::from_utf8(STANDARD.decode(ca_data)?)?;
+  let root_cert = pem
+    .split("-----END CERTIFICATE-----")
+    .filter_map(|block| block.split("-----BEGIN CERTIFICATE-----").nth(1))
+    .map(|body| STANDARD.decode(body.split_whitespace().collect::<String>()))
+    .collect::<Result<Vec<_>, _>>()?;
+
+  let token = eks::get_cluster_token(aws_shared_config, cluster_name).await?;
+
+  let mut config = kube::Config::new(endpoint.parse()?);
+  config.root_cert = Some(root_cert);
+  config.auth_info.token = Some(SecretString::new(token));
+
+  Ok(kube::Client::try_from(config)?)
+}
+
+/// Analyze the cluster provided to collect all reported findings
+///
+/// `support_window` is the number of days prior to the end of standard support that the
+/// cluster version is reported as approaching the end of support
+pub(crate) async fn analyze(
+  aws_shared_config: &aws_config::SdkConfig,
+  k8s_client: &kube::Client,
+  cluster: &Cluster,
+  catalog: &version::Catalog,
+  support_window: i64,
+) -> Result<Results> {
+  // Construct clients once
+  let asg_client = aws_sdk_autoscaling::Client::new(aws_shared_config);
+  let ec2_client = aws_sdk_ec2::Client::new(aws_shared_config);
+  let eks_client = aws_sdk_eks::Client::new(aws_shared_config);
+
+  let cluster_name = cluster.name().unwrap();
 
-  let cluster_version = cluster.version().unwrap();
-  let target_version = version::get_target_version(cluster_version)?;
+  let cluster_version: version::Version = cluster.version().context("Cluster version not found")?.parse()?;
+  let target_version = cluster_version.next_minor();
 
-  let cluster_findings = eks::get_cluster_findings(cluster).await?;
-  let subnet_findings = eks::get_subnet_findings(&ec2_client, &k8s_client, cluster).await?;
-  let addon_findings = eks::get_addon_findings(&eks_client, cluster_name, cluster_version).await?;
+  let cluster_findings = eks::get_cluster_findings(cluster, &cluster_version, catalog, support_window).await?;
+  let subnet_findings = eks::get_subnet_findings(&ec2_client, k8s_client, cluster).await?;
+  let addons = eks::get_addons(&eks_client, cluster_name).await?;
+  let addon_findings = eks::get_addon_findings(&eks_client, &cluster_version, &addons).await?;
   let dataplane_findings = eks::get_data_plane_findings(&asg_client, &ec2_client, &eks_client, cluster).await?;
-  let kubernetes_findings = k8s::get_kubernetes_findings(&k8s_client, cluster_version, &target_version).await?;
+  let kubernetes_findings =
+    k8s::get_kubernetes_findings(k8s_client, &cluster_version, &target_version, &addons).await?;
 
   Ok(Results {
     cluster: cluster_findings,
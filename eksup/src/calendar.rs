@@ -0,0 +1,212 @@
+use std::fs;
+
+use anyhow::Result;
+use aws_sdk_eks::types::Cluster;
+use k8s_openapi::chrono::{DateTime, Days, NaiveDate, Utc};
+use regex::Regex;
+use tracing::warn;
+
+use crate::{fleet, version, Calendar};
+
+/// Support milestone of a cluster version on Amazon EKS
+#[derive(Debug)]
+struct Deadline {
+  cluster: String,
+  account: String,
+  region: String,
+  version: version::Version,
+  /// Either `standard` or `extended`
+  support: &'static str,
+  date: NaiveDate,
+}
+
+impl Deadline {
+  fn summary(&self) -> String {
+    format!("EKS {} v{} end of {} support", self.cluster, self.version, self.support)
+  }
+
+  fn description(&self) -> String {
+    let target = self.version.next_minor();
+    let consequence = match self.support {
+      "standard" => "the cluster enters extended support and incurs additional cost",
+      _ => "the cluster control plane is automatically upgraded by Amazon EKS",
+    };
+
+    format!(
+      "Amazon EKS cluster {} (account {}, region {}) is running Kubernetes v{}. \
+      Upgrade the cluster to v{target} before {}, after which {consequence}.",
+      self.cluster, self.account, self.region, self.version, self.date
+    )
+  }
+}
+
+/// Returns the upcoming end of support deadlines for the cluster, as of the date provided
+fn get_deadlines(cluster: &Cluster, catalog: &version::Catalog, date: NaiveDate) -> Vec<Deadline> {
+  let cluster_version = match cluster.version().unwrap_or_default().parse() {
+    Ok(version) => version,
+    Err(err) => {
+      warn!("Skipping cluster {}: {err}", cluster.name().unwrap_or_default());
+      return vec![];
+    }
+  };
+  let release = match catalog.get(&cluster_version) {
+    Some(release) => release,
+    None => return vec![],
+  };
+  let (account, region) = fleet::parse_cluster_arn(cluster.arn().unwrap_or_default());
+
+  [
+    ("standard", release.end_of_standard_support),
+    ("extended", release.end_of_extended_support),
+  ]
+  .into_iter()
+  .filter_map(|(support, end)| end.filter(|end| *end >= date).map(|end| (support, end)))
+  .map(|(support, end)| Deadline {
+    cluster: cluster.name().unwrap_or_default().to_owned(),
+    account: account.to_owned(),
+    region: region.to_owned(),
+    version: cluster_version,
+    support,
+    date: end,
+  })
+  .collect()
+}
+
+/// Escape text values per RFC 5545 section 3.3.11
+fn escape(text: &str) -> String {
+  text
+    .replace('\\', "\\\\")
+    .replace(';', "\\;")
+    .replace(',', "\\,")
+    .replace('\n', "\\n")
+}
+
+/// Fold content lines longer than 75 octets per RFC 5545 section 3.1
+fn fold(line: &str) -> String {
+  let mut folded = String::new();
+  let mut length = 0;
+
+  for c in line.chars() {
+    if length + c.len_utf8() > 75 {
+      folded.push_str("\r\n ");
+      length = 1;
+    }
+    folded.push(c);
+    length += c.len_utf8();
+  }
+
+  folded
+}
+
+/// Render the deadlines as an iCalendar (.ics) document
+///
+/// Each deadline is an all-day event with a reminder `reminder` days prior to the deadline
+fn to_ics(deadlines: &[Deadline], timestamp: DateTime<Utc>, reminder: u64) -> String {
+  let stamp = timestamp.format("%Y%m%dT%H%M%SZ").to_string();
+  let mut lines = vec![
+    "BEGIN:VCALENDAR".to_string(),
+    "VERSION:2.0".to_string(),
+    "PRODID:-//clowdhaus//eksup//EN".to_string(),
+    "CALSCALE:GREGORIAN".to_string(),
+  ];
+
+  for deadline in deadlines {
+    let end = deadline.date.checked_add_days(Days::new(1)).unwrap_or(deadline.date);
+
+    lines.extend([
+      "BEGIN:VEVENT".to_string(),
+      format!(
+        "UID:{}-{}-{}-{}-{}@eksup",
+        deadline.account, deadline.region, deadline.cluster, deadline.version, deadline.support
+      ),
+      format!("DTSTAMP:{stamp}"),
+      format!("DTSTART;VALUE=DATE:{}", deadline.date.format("%Y%m%d")),
+      format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")),
+      format!("SUMMARY:{}", escape(&deadline.summary())),
+      format!("DESCRIPTION:{}", escape(&deadline.description())),
+      "BEGIN:VALARM".to_string(),
+      "ACTION:DISPLAY".to_string(),
+      format!("DESCRIPTION:{}", escape(&deadline.summary())),
+      format!("TRIGGER:-P{reminder}D"),
+      "END:VALARM".to_string(),
+      "END:VEVENT".to_string(),
+    ]);
+  }
+  lines.push("END:VCALENDAR".to_string());
+
+  lines.iter().map(|line| fold(line) + "\r\n").collect()
+}
+
+/// Create an iCalendar file of the upcoming end of support deadlines for the clusters selected
+pub(crate) async fn create(args: &Calendar) -> Result<()> {
+  let selector = fleet::Selector {
+    name_filter: args.name_filter.as_deref().map(Regex::new).transpose()?,
+    tags: args.tags.to_owned(),
+    profiles: args.profiles.to_owned(),
+    accounts: args.accounts.to_owned(),
+    regions: match &args.region {
+      Some(region) => vec![region.to_owned()],
+      None => args.regions.to_owned(),
+    },
+    role_name: args.role_name.to_owned(),
+  };
+  let catalog = version::Catalog::load(&args.data_file)?;
+  let now = Utc::now();
+
+  let mut deadlines = Vec::new();
+  for discovered in fleet::discover(&selector).await? {
+    let clusters = match discovered.clusters {
+      Ok(clusters) => clusters,
+      Err(err) => {
+        warn!("{err:#}");
+        continue;
+      }
+    };
+
+    for cluster in clusters
+      .iter()
+      .filter(|cluster| args.cluster.is_empty() || args.cluster.iter().any(|name| cluster.name() == Some(name)))
+    {
+      deadlines.extend(get_deadlines(cluster, &catalog, now.date_naive()));
+    }
+  }
+  deadlines.sort_by_key(|deadline| deadline.date);
+
+  let calendar = to_ics(&deadlines, now, args.reminder);
+  match &args.output {
+    Some(filename) => fs::write(filename, calendar)?,
+    None => print!("{calendar}"),
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn can_create_calendar() {
+    let catalog = version::Catalog::load(&None).unwrap();
+    let cluster = Cluster::builder()
+      .name("example")
+      .arn("arn:aws:eks:us-east-1:111122223333:cluster/example")
+      .version("1.28")
+      .build();
+
+    let date = NaiveDate::from_ymd_opt(2024, 12, 1).unwrap();
+    let deadlines = get_deadlines(&cluster, &catalog, date);
+    // End of standard support (2024-11-26) has passed, only the end of extended support remains
+    assert_eq!(deadlines.len(), 1);
+    assert_eq!(deadlines[0].support, "extended");
+
+    let timestamp = DateTime::<Utc>::from_utc(date.and_hms_opt(0, 0, 0).unwrap(), Utc);
+    let calendar = to_ics(&deadlines, timestamp, 30);
+
+    assert!(calendar.starts_with("BEGIN:VCALENDAR\r\n"));
+    assert!(calendar.ends_with("END:VCALENDAR\r\n"));
+    assert!(calendar.contains("DTSTART;VALUE=DATE:20251126\r\n"));
+    assert!(calendar.contains("TRIGGER:-P30D\r\n"));
+    assert!(calendar.lines().all(|line| line.len() <= 75));
+  }
+}
@@ -6,6 +6,7 @@ use aws_sdk_eks::{
   Client as EksClient,
 };
 use itertools::Itertools;
+use k8s_openapi::chrono::NaiveDate;
 use kube::Client as K8sClient;
 use serde::{Deserialize, Serialize};
 use tabled::{
@@ -15,7 +16,7 @@ use tabled::{
 
 use crate::{
   eks::resources,
-  finding::{self, Findings},
+  finding::{self, Findings, ToEntry},
   k8s,
   output::tabled_vec_to_string,
   version,
@@ -34,6 +35,12 @@ pub struct ClusterHealthIssue {
   pub resource_ids: Vec<String>,
 }
 
+impl ToEntry for ClusterHealthIssue {
+  fn to_entry(&self) -> finding::Entry {
+    finding::Entry::new(&self.finding, "Cluster", &self.code)
+  }
+}
+
 impl Findings for Vec<ClusterHealthIssue> {
   fn to_markdown_table(&self, leading_whitespace: &str) -> Result<String> {
     if self.is_empty() {
@@ -98,6 +105,100 @@ pub(crate) async fn cluster_health(cluster: &Cluster) -> Result<Vec<ClusterHealt
   }
 }
 
+fn display_date(date: &Option<NaiveDate>) -> String {
+  date.map(|date| date.to_string()).unwrap_or_default()
+}
+
+/// Support status of the cluster's current version on Amazon EKS
+#[derive(Debug, Serialize, Deserialize, Tabled)]
+#[tabled(rename_all = "UpperCase")]
+pub struct EndOfSupport {
+  #[tabled(inline)]
+  pub finding: finding::Finding,
+  pub version: String,
+  /// Support phase of the version - either `standard`, `extended`, or `ended`
+  pub support: String,
+  #[tabled(display_with = "display_date")]
+  pub end_of_standard_support: Option<NaiveDate>,
+  #[tabled(display_with = "display_date")]
+  pub end_of_extended_support: Option<NaiveDate>,
+  /// Number of days remaining in the current support phase, negative once support has ended
+  pub days_remaining: i64,
+}
+
+impl ToEntry for EndOfSupport {
+  fn to_entry(&self) -> finding::Entry {
+    finding::Entry::new(&self.finding, "Cluster", &self.version)
+  }
+}
+
+impl Findings for Vec<EndOfSupport> {
+  fn to_markdown_table(&self, leading_whitespace: &str) -> Result<String> {
+    if self.is_empty() {
+      return Ok(format!(
+        "{leading_whitespace}✅ - The cluster version is not approaching the end of standard support"
+      ));
+    }
+
+    let mut table = Table::new(self);
+    table
+      .with(Disable::column(ByColumnName::new("CHECK")))
+      .with(Margin::new(1, 0, 0, 0).fill('\t', 'x', 'x', 'x'))
+      .with(Style::markdown());
+
+    Ok(format!("{table}\n"))
+  }
+
+  fn to_stdout_table(&self) -> Result<String> {
+    if self.is_empty() {
+      return Ok("".to_owned());
+    }
+
+    let mut table = Table::new(self);
+    table.with(Style::sharp());
+
+    Ok(format!("{table}\n"))
+  }
+}
+
+/// Check if the cluster version is approaching the end of standard support or is in extended support
+///
+/// A version within `window` days of the end of standard support is reported as a recommendation,
+/// whereas a version in extended support (incurring additional cost) or past the end of support is required
+pub(crate) fn end_of_support(
+  catalog: &version::Catalog,
+  cluster_version: &version::Version,
+  date: NaiveDate,
+  window: i64,
+) -> Result<Vec<EndOfSupport>> {
+  let release = match catalog.get(cluster_version) {
+    Some(release) => release,
+    None => return Ok(vec![]),
+  };
+
+  let (remediation, support, days_remaining) = match release.support(date) {
+    version::Support::Standard(days) if days <= window => (finding::Remediation::Recommended, "standard", days),
+    version::Support::Extended(days) => (finding::Remediation::Required, "extended", days),
+    version::Support::Ended(days) => (finding::Remediation::Required, "ended", -days),
+    _ => return Ok(vec![]),
+  };
+
+  let finding = finding::Finding {
+    code: finding::Code::EKS008,
+    symbol: remediation.symbol(),
+    remediation,
+  };
+
+  Ok(vec![EndOfSupport {
+    finding,
+    version: cluster_version.to_string(),
+    support: support.to_owned(),
+    end_of_standard_support: release.end_of_standard_support,
+    end_of_extended_support: release.end_of_extended_support,
+    days_remaining,
+  }])
+}
+
 /// Subnet details that can affect upgrade behavior
 #[derive(Debug, Serialize, Deserialize, Tabled)]
 #[tabled(rename_all = "UpperCase")]
@@ -108,6 +209,12 @@ pub struct InsufficientSubnetIps {
   pub available_ips: i32,
 }
 
+impl ToEntry for InsufficientSubnetIps {
+  fn to_entry(&self) -> finding::Entry {
+    finding::Entry::new(&self.finding, "AvailabilityZone", &self.id)
+  }
+}
+
 impl Findings for Vec<InsufficientSubnetIps> {
   fn to_markdown_table(&self, leading_whitespace: &str) -> Result<String> {
     if self.is_empty() {
@@ -268,6 +375,12 @@ pub struct AddonVersionCompatibility {
   pub target_kubernetes_version: resources::AddonVersion,
 }
 
+impl ToEntry for AddonVersionCompatibility {
+  fn to_entry(&self) -> finding::Entry {
+    finding::Entry::new(&self.finding, "Addon", &self.name)
+  }
+}
+
 impl Findings for Vec<AddonVersionCompatibility> {
   fn to_markdown_table(&self, leading_whitespace: &str) -> Result<String> {
     if self.is_empty() {
@@ -300,17 +413,17 @@ impl Findings for Vec<AddonVersionCompatibility> {
 /// Check for any version compatibility issues for the EKS addons enabled
 pub(crate) async fn addon_version_compatibility(
   client: &EksClient,
-  cluster_version: &str,
+  cluster_version: &version::Version,
   addons: &[Addon],
 ) -> Result<Vec<AddonVersionCompatibility>> {
   let mut addon_versions = Vec::new();
-  let target_k8s_version = format!("1.{}", version::parse_minor(cluster_version)? + 1);
+  let target_k8s_version = cluster_version.next_minor().to_string();
 
   for addon in addons {
     let name = addon.addon_name().unwrap_or_default().to_owned();
     let version = addon.addon_version().unwrap_or_default().to_owned();
 
-    let current_kubernetes_version = resources::get_addon_versions(client, &name, cluster_version).await?;
+    let current_kubernetes_version = resources::get_addon_versions(client, &name, &cluster_version.to_string()).await?;
     let target_kubernetes_version = resources::get_addon_versions(client, &name, &target_k8s_version).await?;
 
     // TODO - why is this saying the if/else is the same?
@@ -363,6 +476,12 @@ pub struct AddonHealthIssue {
   pub resource_ids: Vec<String>,
 }
 
+impl ToEntry for AddonHealthIssue {
+  fn to_entry(&self) -> finding::Entry {
+    finding::Entry::new(&self.finding, "Addon", &self.name)
+  }
+}
+
 impl Findings for Vec<AddonHealthIssue> {
   fn to_markdown_table(&self, leading_whitespace: &str) -> Result<String> {
     if self.is_empty() {
@@ -447,6 +566,12 @@ pub struct NodegroupHealthIssue {
   pub message: String,
 }
 
+impl ToEntry for NodegroupHealthIssue {
+  fn to_entry(&self) -> finding::Entry {
+    finding::Entry::new(&self.finding, "Nodegroup", &self.name)
+  }
+}
+
 impl Findings for Vec<NodegroupHealthIssue> {
   fn to_markdown_table(&self, leading_whitespace: &str) -> Result<String> {
     if self.is_empty() {
@@ -540,6 +665,12 @@ pub struct ManagedNodeGroupUpdate {
   // launch_configuration_name: Option<String>,
 }
 
+impl ToEntry for ManagedNodeGroupUpdate {
+  fn to_entry(&self) -> finding::Entry {
+    finding::Entry::new(&self.finding, "Nodegroup", &self.name)
+  }
+}
+
 impl Findings for Vec<ManagedNodeGroupUpdate> {
   fn to_markdown_table(&self, leading_whitespace: &str) -> Result<String> {
     if self.is_empty() {
@@ -640,6 +771,12 @@ pub struct AutoscalingGroupUpdate {
   // launch_configuration_name: Option<String>,
 }
 
+impl ToEntry for AutoscalingGroupUpdate {
+  fn to_entry(&self) -> finding::Entry {
+    finding::Entry::new(&self.finding, "AutoScalingGroup", &self.name)
+  }
+}
+
 impl Findings for Vec<AutoscalingGroupUpdate> {
   fn to_markdown_table(&self, leading_whitespace: &str) -> Result<String> {
     if self.is_empty() {
@@ -1,24 +1,45 @@
 use anyhow::Result;
 use aws_sdk_autoscaling::Client as AsgClient;
 use aws_sdk_ec2::Client as Ec2Client;
-use aws_sdk_eks::{types::Cluster, Client as EksClient};
+use aws_sdk_eks::{
+  types::{Addon, Cluster},
+  Client as EksClient,
+};
+use k8s_openapi::chrono::Utc;
 use kube::Client as K8sClient;
 use serde::{Deserialize, Serialize};
 
-use crate::eks::{checks, resources};
+use crate::{
+  eks::{checks, resources},
+  version,
+};
 
 /// Findings related to the cluster itself, primarily the control plane
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ClusterFindings {
   /// The health of the cluster as reported by the Amazon EKS API
   pub cluster_health: Vec<checks::ClusterHealthIssue>,
+  /// The support status of the cluster version, when approaching or past the end of standard support
+  pub end_of_support: Vec<checks::EndOfSupport>,
 }
 
 /// Collects the cluster findings from the Amazon EKS API
-pub async fn get_cluster_findings(cluster: &Cluster) -> Result<ClusterFindings> {
+///
+/// `support_window` is the number of days prior to the end of standard support that the
+/// cluster version is reported as approaching the end of support
+pub async fn get_cluster_findings(
+  cluster: &Cluster,
+  cluster_version: &version::Version,
+  catalog: &version::Catalog,
+  support_window: i64,
+) -> Result<ClusterFindings> {
   let cluster_health = checks::cluster_health(cluster).await?;
+  let end_of_support = checks::end_of_support(catalog, cluster_version, Utc::now().date_naive(), support_window)?;
 
-  Ok(ClusterFindings { cluster_health })
+  Ok(ClusterFindings {
+    cluster_health,
+    end_of_support,
+  })
 }
 
 /// Networking/subnet findings, primarily focused on IP exhaustion/number of available IPs
@@ -73,13 +94,11 @@ pub struct AddonFindings {
 /// Collects the addon findings from the Amazon EKS addon API
 pub async fn get_addon_findings(
   eks_client: &EksClient,
-  cluster_name: &str,
-  cluster_version: &str,
+  cluster_version: &version::Version,
+  addons: &[Addon],
 ) -> Result<AddonFindings> {
-  let addons = resources::get_addons(eks_client, cluster_name).await?;
-
-  let version_compatibility = checks::addon_version_compatibility(eks_client, cluster_version, &addons).await?;
-  let health = checks::addon_health(&addons).await?;
+  let version_compatibility = checks::addon_version_compatibility(eks_client, cluster_version, addons).await?;
+  let health = checks::addon_health(addons).await?;
 
   Ok(AddonFindings {
     version_compatibility,
@@ -6,4 +6,4 @@ pub use findings::{
   get_addon_findings, get_cluster_findings, get_data_plane_findings, get_subnet_findings, AddonFindings,
   ClusterFindings, DataPlaneFindings, SubnetFindings,
 };
-pub use resources::get_cluster;
+pub use resources::{get_addons, get_cluster, get_cluster_token, get_clusters};
@@ -1,6 +1,11 @@
-use std::{collections::HashSet, process::exit};
+use std::{
+  collections::HashSet,
+  process::exit,
+  time::{Duration, SystemTime},
+};
 
 use anyhow::{Context, Result};
+use aws_credential_types::provider::ProvideCredentials;
 use aws_sdk_autoscaling::{
   types::{AutoScalingGroup, Filter as AsgFilter},
   Client as AsgClient,
@@ -10,6 +15,8 @@ use aws_sdk_eks::{
   types::{Addon, Cluster, FargateProfile, Nodegroup},
   Client as EksClient,
 };
+use aws_sigv4::http_request::{sign, SignableRequest, SignatureLocation, SigningParams, SigningSettings};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use serde::{Deserialize, Serialize};
 use tabled::Tabled;
 use tracing::error;
@@ -31,6 +38,79 @@ pub async fn get_cluster(client: &EksClient, name: &str) -> Result<Cluster> {
   }
 }
 
+/// Describe all of the clusters in the account and region of the client
+pub async fn get_clusters(client: &EksClient) -> Result<Vec<Cluster>> {
+  let mut names = Vec::new();
+  let mut next_token: Option<String> = None;
+
+  loop {
+    let response = client
+      .list_clusters()
+      .max_results(100)
+      .set_next_token(next_token)
+      .send()
+      .await?;
+
+    names.extend(response.clusters().unwrap_or_default().to_owned());
+    next_token = response.next_token().map(|token| token.to_owned());
+    if next_token.is_none() {
+      break;
+    }
+  }
+
+  let mut clusters = Vec::new();
+  for name in &names {
+    let response = client.describe_cluster().name(name).send().await?;
+    if let Some(cluster) = response.cluster {
+      clusters.push(cluster);
+    }
+  }
+
+  Ok(clusters)
+}
+
+/// Generate a bearer token to authenticate with the Kubernetes API of the cluster
+///
+/// This is the same token as generated by `aws eks get-token`; a pre-signed STS `GetCallerIdentity`
+/// request that includes the cluster name, which the cluster uses to verify the IAM identity
+/// <https://github.com/kubernetes-sigs/aws-iam-authenticator#api-authorization-from-outside-a-cluster>
+pub async fn get_cluster_token(aws_shared_config: &aws_config::SdkConfig, cluster_name: &str) -> Result<String> {
+  let region = aws_shared_config.region().context("AWS region not found")?.to_string();
+  let credentials = aws_shared_config
+    .credentials_provider()
+    .context("AWS credentials not found")?
+    .provide_credentials()
+    .await?;
+
+  let mut settings = SigningSettings::default();
+  settings.signature_location = SignatureLocation::QueryParams;
+  settings.expires_in = Some(Duration::from_secs(60));
+
+  let mut params = SigningParams::builder()
+    .access_key(credentials.access_key_id())
+    .secret_key(credentials.secret_access_key())
+    .region(&region)
+    .service_name("sts")
+    .time(SystemTime::now())
+    .settings(settings);
+  params.set_security_token(credentials.session_token());
+  let params = params.build()?;
+
+  let mut request = http::Request::get(format!(
+    "https://sts.{region}.amazonaws.com/?Action=GetCallerIdentity&Version=2011-06-15"
+  ))
+  .header("x-k8s-aws-id", cluster_name)
+  .body("")?;
+
+  let (instructions, _signature) = sign(SignableRequest::from(&request), &params)?.into_parts();
+  instructions.apply_to_request(&mut request);
+
+  Ok(format!(
+    "k8s-aws-v1.{}",
+    URL_SAFE_NO_PAD.encode(request.uri().to_string())
+  ))
+}
+
 /// Container for the subnet IDs and their total available IPs
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub(crate) struct VpcSubnet {
@@ -133,7 +213,7 @@ pub(crate) async fn get_addon_versions(
     .await?;
 
   // Since we are providing an addon name, we are only concerned with the first and only item
-  let addon = describe.addons().unwrap_or_default().get(0).unwrap();
+  let addon = describe.addons().unwrap_or_default().first().unwrap();
   let latest_version = match addon.addon_versions() {
     Some(versions) => match versions.first() {
       Some(version) => version.addon_version().unwrap_or_default(),
@@ -319,3 +399,38 @@ pub(crate) async fn get_launch_template(client: &Ec2Client, id: &str) -> Result<
     None => exit(1),
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use aws_credential_types::{provider::SharedCredentialsProvider, Credentials};
+  use aws_types::region::Region;
+
+  use super::*;
+
+  #[tokio::test]
+  async fn can_get_cluster_token() {
+    let config = aws_config::SdkConfig::builder()
+      .region(Region::new("us-east-1"))
+      .credentials_provider(SharedCredentialsProvider::new(Credentials::new(
+        "AKIDEXAMPLE",
+        "secret",
+        None,
+        None,
+        "test",
+      )))
+      .build();
+
+    let token = get_cluster_token(&config, "example").await.unwrap();
+    let url = String::from_utf8(
+      URL_SAFE_NO_PAD
+        .decode(token.strip_prefix("k8s-aws-v1.").unwrap())
+        .unwrap(),
+    )
+    .unwrap();
+
+    assert!(url.starts_with("https://sts.us-east-1.amazonaws.com/?Action=GetCallerIdentity&Version=2011-06-15"));
+    assert!(url.contains("X-Amz-Expires=60"));
+    assert!(url.contains("X-Amz-SignedHeaders=host%3Bx-k8s-aws-id"));
+    assert!(url.contains("X-Amz-Signature="));
+  }
+}
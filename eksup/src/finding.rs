@@ -1,4 +1,5 @@
 use anyhow::Result;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use tabled::Tabled;
 
@@ -18,7 +19,7 @@ pub struct Finding {
 /// Determines whether remediation is required or recommended
 ///
 /// This allows for filtering of findings shown to user
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub enum Remediation {
   /// A finding that requires remediation prior to upgrading to be able to perform the upgrade
   /// and avoid downtime or disruption
@@ -52,30 +53,94 @@ pub trait Findings {
   fn to_stdout_table(&self) -> Result<String>;
 }
 
+/// A finding flattened into a shape that is common across all checks
+///
+/// This is used where findings from different checks need to be handled uniformly,
+/// such as summarizing the results or writing the findings back into the cluster
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Entry {
+  pub code: Code,
+  pub remediation: Remediation,
+  /// API version of the Kubernetes resource the finding is reported on, if applicable
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub api_version: Option<String>,
+  /// Kind of the resource the finding is reported on (i.e. - `Deployment`, `Addon`, etc.)
+  pub kind: String,
+  /// Namespace of the Kubernetes resource the finding is reported on, if applicable
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub namespace: Option<String>,
+  /// Name of the resource the finding is reported on
+  pub name: String,
+}
+
+impl Entry {
+  /// Creates an entry for a resource that is not a namespaced Kubernetes resource
+  pub(crate) fn new(finding: &Finding, kind: &str, name: &str) -> Self {
+    Entry {
+      code: finding.code.to_owned(),
+      remediation: finding.remediation.to_owned(),
+      api_version: None,
+      kind: kind.to_owned(),
+      namespace: None,
+      name: name.to_owned(),
+    }
+  }
+}
+
+pub trait ToEntry {
+  fn to_entry(&self) -> Entry;
+}
+
+/// Converts a collection of findings into their common entry representation
+pub(crate) fn to_entries<T: ToEntry>(findings: &[T]) -> Vec<Entry> {
+  findings.iter().map(|f| f.to_entry()).collect()
+}
+
+/// Count of findings by remediation type
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct Summary {
+  pub required: usize,
+  pub recommended: usize,
+}
+
+impl Summary {
+  pub(crate) fn new(entries: &[Entry]) -> Self {
+    entries.iter().fold(Summary::default(), |mut summary, entry| {
+      match entry.remediation {
+        Remediation::Required => summary.required += 1,
+        Remediation::Recommended => summary.recommended += 1,
+      }
+      summary
+    })
+  }
+}
+
 /// TODO - something is required to identify what Kubernetes resource findings are applicable
 /// TODO - to specific version. For example, if a user is already on version 1.23, then they should
 /// TODO - not be shown findings that affect version <= 1.22
+#[allow(dead_code)]
 pub(crate) trait Deprecation {
   /// Returns the Kubernetes version the check was deprecated in
-  fn deprecated_in(&self) -> Option<version::KubernetesVersion>;
+  fn deprecated_in(&self) -> Option<version::Version>;
   /// Returns the Kubernetes version the check will be removed in
-  fn removed_in(&self) -> Option<version::KubernetesVersion>;
+  fn removed_in(&self) -> Option<version::Version>;
 }
 
 /// Codes that represent the finding variants
 ///
 /// This is useful for a few reasons:
 /// 1. It would allow users to add codes to a 'ignore list' in the future, to ignore any
-/// reported findings of that code type (another level of granularity of what data is
-/// is most relevant to them)
+///    reported findings of that code type (another level of granularity of what data is
+///    is most relevant to them)
 /// 2. It provides a "marker" that can be used to link to documentation for the finding,
-/// keeping the direct output concise while still providing the means for a full explanation
-/// and reasoning behind the finding in one location
+///    keeping the direct output concise while still providing the means for a full explanation
+///    and reasoning behind the finding in one location
 /// 3. It provides a strongly typed link between code and finding data allowing the code
-/// to uniquely represent a finding even if the finding data is generic (i.e. - as is the case
-/// in reporting available IPs as subnet findings, the data shape is generic by the finding
-/// is unique to different scenarios)
-#[derive(Clone, Debug, Serialize, Deserialize)]
+///    to uniquely represent a finding even if the finding data is generic (i.e. - as is the case
+///    in reporting available IPs as subnet findings, the data shape is generic by the finding
+///    is unique to different scenarios)
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub enum Code {
   /// AWS finding codes not specific to EKS
   ///
@@ -117,6 +182,9 @@ pub enum Code {
   /// Self-managed node group autoscaling group has pending update(s)
   EKS007,
 
+  /// Cluster version is approaching the end of standard support, or is in extended support
+  EKS008,
+
   /// Kubernetes finding codes not specific to EKS
   ///
   /// Kubernetes version skew detected between control plane and node
@@ -131,10 +199,10 @@ pub enum Code {
   /// Missing `podDisruptionBudgets`
   K8S004,
 
-  /// Pod distribution settings put availability at risk
+  /// Pod distribution settings put availability at risk (i.e. - pods are not spread across availability zones)
   K8S005,
 
-  /// `pod.spec.containers[*].readinessProbe` not set
+  /// `pod.spec.containers[*].readinessProbe` not set, or `livenessProbe` puts availability at risk
   K8S006,
 
   /// `pod.spec.TerminationGracePeriodSeconds` is set to zero
@@ -151,6 +219,39 @@ pub enum Code {
 
   /// Kubernetes version skew detected between kube-proxy and kubelet
   K8S011,
+
+  /// Removed Kubernetes API(s) still in use
+  K8S012,
+
+  /// Helm release history contains removed Kubernetes API(s)
+  K8S013,
+
+  /// `podDisruptionBudget` prevents pods from being evicted
+  K8S014,
+
+  /// Amazon EBS `gp2` volumes in use
+  K8S015,
+
+  /// Running replicas are placed on a single node, nodegroup, or availability zone
+  K8S016,
+
+  /// Pod is not managed by a controller
+  K8S017,
+
+  /// Pod blocks, or loses data on, node drains
+  K8S018,
+
+  /// Admission webhook that fails closed can deadlock the cluster
+  K8S019,
+
+  /// CustomResourceDefinition stored versions, conversion webhook, or schema will fail upgrades
+  K8S020,
+
+  /// APIService is not available
+  K8S021,
+
+  /// Node is not ready, under pressure, or cordoned
+  K8S022,
 }
 
 impl std::fmt::Display for Code {
@@ -168,6 +269,7 @@ impl std::fmt::Display for Code {
       Code::EKS005 => write!(f, "EKS005"),
       Code::EKS006 => write!(f, "EKS006"),
       Code::EKS007 => write!(f, "EKS007"),
+      Code::EKS008 => write!(f, "EKS008"),
       Code::K8S001 => write!(f, "K8S001"),
       Code::K8S002 => write!(f, "K8S002"),
       Code::K8S003 => write!(f, "K8S003"),
@@ -179,6 +281,44 @@ impl std::fmt::Display for Code {
       Code::K8S009 => write!(f, "K8S009"),
       Code::K8S010 => write!(f, "K8S010"),
       Code::K8S011 => write!(f, "K8S011"),
+      Code::K8S012 => write!(f, "K8S012"),
+      Code::K8S013 => write!(f, "K8S013"),
+      Code::K8S014 => write!(f, "K8S014"),
+      Code::K8S015 => write!(f, "K8S015"),
+      Code::K8S016 => write!(f, "K8S016"),
+      Code::K8S017 => write!(f, "K8S017"),
+      Code::K8S018 => write!(f, "K8S018"),
+      Code::K8S019 => write!(f, "K8S019"),
+      Code::K8S020 => write!(f, "K8S020"),
+      Code::K8S021 => write!(f, "K8S021"),
+      Code::K8S022 => write!(f, "K8S022"),
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn entry(code: Code, remediation: Remediation) -> Entry {
+    let finding = Finding {
+      code,
+      symbol: remediation.symbol(),
+      remediation,
+    };
+    Entry::new(&finding, "Deployment", "web")
+  }
+
+  #[test]
+  fn can_summarize_entries() {
+    let summary = Summary::new(&[
+      entry(Code::K8S002, Remediation::Required),
+      entry(Code::K8S003, Remediation::Recommended),
+      entry(Code::K8S004, Remediation::Required),
+    ]);
+    assert_eq!((summary.required, summary.recommended), (2, 1));
+
+    let summary = Summary::new(&[]);
+    assert_eq!((summary.required, summary.recommended), (0, 0));
+  }
+}
@@ -0,0 +1,345 @@
+use std::fmt;
+
+use anyhow::{Context, Result};
+use aws_config::sts::AssumeRoleProvider;
+use aws_sdk_eks::types::Cluster;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tabled::{settings::Style, Table, Tabled};
+
+use crate::{analysis, eks, finding, output, version};
+
+/// Parse a tag filter provided in the form of `key=value`
+pub(crate) fn parse_tag(tag: &str) -> Result<(String, String)> {
+  let (key, value) = tag
+    .split_once('=')
+    .context("Tag filter must be in the form of `key=value`")?;
+
+  Ok((key.to_owned(), value.to_owned()))
+}
+
+/// The AWS profile, account, and region that clusters are discovered and analyzed within
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Scope {
+  pub profile: Option<String>,
+  pub account: Option<String>,
+  pub region: Option<String>,
+}
+
+impl fmt::Display for Scope {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let mut parts = Vec::new();
+    if let Some(profile) = &self.profile {
+      parts.push(format!("profile {profile}"));
+    }
+    if let Some(account) = &self.account {
+      parts.push(format!("account {account}"));
+    }
+    if let Some(region) = &self.region {
+      parts.push(format!("region {region}"));
+    }
+
+    match parts.is_empty() {
+      true => write!(f, "the default account and region"),
+      false => write!(f, "{}", parts.join(", ")),
+    }
+  }
+}
+
+/// Returns the combination of every profile, account, and region provided
+///
+/// Any of the lists may be empty, in which case the default (profile, account, or region)
+/// of the environment is used
+pub(crate) fn get_scopes(profiles: &[String], accounts: &[String], regions: &[String]) -> Vec<Scope> {
+  let or_default = |values: &[String]| -> Vec<Option<String>> {
+    match values.is_empty() {
+      true => vec![None],
+      false => values.iter().map(|value| Some(value.to_owned())).collect(),
+    }
+  };
+
+  let mut scopes = Vec::new();
+  for profile in or_default(profiles) {
+    for account in or_default(accounts) {
+      for region in or_default(regions) {
+        scopes.push(Scope {
+          profile: profile.to_owned(),
+          account: account.to_owned(),
+          region,
+        });
+      }
+    }
+  }
+
+  scopes
+}
+
+/// Criteria used to discover the clusters of a multi-cluster analysis
+#[derive(Debug, Default)]
+pub(crate) struct Selector {
+  /// Only clusters whose name matches the regular expression are selected
+  pub name_filter: Option<Regex>,
+  /// Only clusters with all of the tags are selected
+  pub tags: Vec<(String, String)>,
+  pub profiles: Vec<String>,
+  pub accounts: Vec<String>,
+  pub regions: Vec<String>,
+  /// The name of the IAM role assumed in each of the accounts
+  pub role_name: Option<String>,
+}
+
+/// The clusters discovered within a scope, along with the configuration used to access them
+pub(crate) struct Discovered {
+  pub scope: Scope,
+  pub region: String,
+  pub aws_config: aws_config::SdkConfig,
+  /// The clusters matching the selector, or the error encountered while listing the clusters
+  pub clusters: Result<Vec<Cluster>>,
+}
+
+/// Discover the clusters matching the selector across each of the scopes of the selector
+///
+/// An error listing the clusters of a scope (i.e. - unable to assume the role) is captured
+/// on that scope to avoid one scope from halting the discovery of the others
+pub(crate) async fn discover(selector: &Selector) -> Result<Vec<Discovered>> {
+  let mut discovered = Vec::new();
+
+  for scope in get_scopes(&selector.profiles, &selector.accounts, &selector.regions) {
+    let aws_config = get_scope_config(&scope, &selector.role_name).await?;
+    let region = aws_config.region().map(|region| region.to_string()).unwrap_or_default();
+    let eks_client = aws_sdk_eks::Client::new(&aws_config);
+
+    let clusters = eks::get_clusters(&eks_client)
+      .await
+      .map(|clusters| {
+        clusters
+          .into_iter()
+          .filter(|cluster| matches(cluster, &selector.name_filter, &selector.tags))
+          .collect()
+      })
+      .with_context(|| format!("Unable to list clusters in {scope}"));
+
+    discovered.push(Discovered {
+      scope,
+      region,
+      aws_config,
+      clusters,
+    });
+  }
+
+  Ok(discovered)
+}
+
+/// Get the configuration for the profile, account, and region of a multi-cluster analysis
+///
+/// When an account is provided, the role is assumed in that account using the credentials of the profile (or environment)
+async fn get_scope_config(scope: &Scope, role_name: &Option<String>) -> Result<aws_config::SdkConfig> {
+  let config = crate::get_config(&scope.region, &scope.profile).await?;
+
+  let (account, role_name) = match (&scope.account, role_name) {
+    (Some(account), Some(role_name)) => (account, role_name),
+    _ => return Ok(config),
+  };
+
+  let region = config.region().context("AWS region not found")?.to_owned();
+  let credentials = config
+    .credentials_provider()
+    .context("AWS credentials not found")?
+    .to_owned();
+  let role_arn = format!("arn:{}:iam::{account}:role/{role_name}", get_partition(region.as_ref()));
+  let provider = AssumeRoleProvider::builder(role_arn)
+    .session_name("eksup")
+    .region(region.to_owned())
+    .build(credentials);
+
+  Ok(
+    aws_config::from_env()
+      .region(region)
+      .credentials_provider(provider)
+      .load()
+      .await,
+  )
+}
+
+/// Returns the AWS partition of the region, used to construct ARNs
+fn get_partition(region: &str) -> &'static str {
+  if region.starts_with("cn-") {
+    "aws-cn"
+  } else if region.starts_with("us-gov-") {
+    "aws-us-gov"
+  } else {
+    "aws"
+  }
+}
+
+/// Returns the account ID and region from a cluster ARN
+///
+/// `arn:aws:eks:us-east-1:111122223333:cluster/example` -> (`111122223333`, `us-east-1`)
+pub(crate) fn parse_cluster_arn(arn: &str) -> (String, String) {
+  let parts: Vec<&str> = arn.split(':').collect();
+  let region = parts.get(3).copied().unwrap_or_default().to_owned();
+  let account = parts.get(4).copied().unwrap_or_default().to_owned();
+
+  (account, region)
+}
+
+/// Returns true if the cluster name matches the name filter and the cluster has all of the tags provided
+pub(crate) fn matches(cluster: &Cluster, name_filter: &Option<Regex>, tags: &[(String, String)]) -> bool {
+  let name = cluster.name().unwrap_or_default();
+  if let Some(name_filter) = name_filter {
+    if !name_filter.is_match(name) {
+      return false;
+    }
+  }
+
+  let cluster_tags = cluster.tags();
+  tags.iter().all(|(key, value)| {
+    cluster_tags
+      .and_then(|cluster_tags| cluster_tags.get(key))
+      .map(|cluster_value| cluster_value == value)
+      .unwrap_or(false)
+  })
+}
+
+fn display_error(error: &Option<String>) -> String {
+  error.to_owned().unwrap_or_default()
+}
+
+/// Summary of the analysis results for a single cluster within the fleet
+#[derive(Debug, Serialize, Deserialize, Tabled)]
+#[tabled(rename_all = "UpperCase")]
+pub(crate) struct ClusterSummary {
+  pub account: String,
+  pub region: String,
+  #[tabled(rename = "CLUSTER")]
+  pub name: String,
+  #[tabled(rename = "CURRENT")]
+  pub version: String,
+  #[tabled(rename = "TARGET")]
+  pub target_version: String,
+  pub required: usize,
+  pub recommended: usize,
+  /// Reported when the cluster could not be analyzed
+  #[tabled(display_with = "display_error")]
+  pub error: Option<String>,
+}
+
+impl ClusterSummary {
+  pub(crate) fn new(cluster: &Cluster, results: Result<analysis::Results>) -> Self {
+    let version = cluster.version().unwrap_or_default().to_owned();
+    let target_version = version
+      .parse::<version::Version>()
+      .map(|version| version.next_minor().to_string())
+      .unwrap_or_default();
+
+    let (summary, error) = match results {
+      Ok(results) => (finding::Summary::new(&results.entries()), None),
+      Err(err) => (finding::Summary::default(), Some(err.to_string())),
+    };
+
+    let (account, region) = parse_cluster_arn(cluster.arn().unwrap_or_default());
+
+    ClusterSummary {
+      account,
+      region,
+      name: cluster.name().unwrap_or_default().to_owned(),
+      version,
+      target_version,
+      required: summary.required,
+      recommended: summary.recommended,
+      error,
+    }
+  }
+
+  /// Summary for a scope where the clusters could not be discovered (i.e. - unable to assume the role)
+  pub(crate) fn from_error(scope: &Scope, region: String, err: anyhow::Error) -> Self {
+    ClusterSummary {
+      account: scope.account.to_owned().unwrap_or_default(),
+      region,
+      name: String::new(),
+      version: String::new(),
+      target_version: String::new(),
+      required: 0,
+      recommended: 0,
+      error: Some(format!("{err:#}")),
+    }
+  }
+}
+
+/// Container of the summarized results for all clusters analyzed
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct FleetResults {
+  pub(crate) clusters: Vec<ClusterSummary>,
+}
+
+impl output::Output for FleetResults {
+  fn to_stdout_table(&self) -> Result<String> {
+    if self.clusters.is_empty() {
+      return Ok("No clusters found matching the filters provided".to_owned());
+    }
+
+    let mut table = Table::new(&self.clusters);
+    table.with(Style::sharp());
+
+    Ok(format!("{table}\n"))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+
+  use super::*;
+
+  #[test]
+  fn can_match_clusters() {
+    let cluster = Cluster::builder()
+      .name("prod-us-east-1")
+      .set_tags(Some(HashMap::from([("env".to_string(), "prod".to_string())])))
+      .build();
+
+    let prod = Some(Regex::new("^prod-").unwrap());
+    let dev = Some(Regex::new("^dev-").unwrap());
+    let env_prod = vec![parse_tag("env=prod").unwrap()];
+    let env_dev = vec![parse_tag("env=dev").unwrap()];
+
+    assert!(matches(&cluster, &None, &[]));
+    assert!(matches(&cluster, &prod, &env_prod));
+    assert!(!matches(&cluster, &dev, &[]));
+    assert!(!matches(&cluster, &None, &env_dev));
+  }
+
+  #[test]
+  fn can_get_scopes() {
+    let profiles = vec!["a".to_string(), "b".to_string()];
+    let accounts = vec!["111122223333".to_string()];
+    let regions = vec!["us-east-1".to_string(), "eu-west-1".to_string()];
+
+    assert_eq!(get_scopes(&[], &[], &[]), vec![Scope::default()]);
+    assert_eq!(get_scopes(&profiles, &accounts, &regions).len(), 4);
+    assert_eq!(
+      get_scopes(&[], &accounts, &regions)[1],
+      Scope {
+        profile: None,
+        account: Some("111122223333".to_string()),
+        region: Some("eu-west-1".to_string()),
+      }
+    );
+  }
+
+  #[test]
+  fn can_parse_cluster_arn() {
+    assert_eq!(
+      parse_cluster_arn("arn:aws:eks:us-east-1:111122223333:cluster/example"),
+      ("111122223333".to_string(), "us-east-1".to_string())
+    );
+    assert_eq!(parse_cluster_arn(""), (String::new(), String::new()));
+  }
+
+  #[test]
+  fn can_parse_tag() {
+    assert_eq!(parse_tag("env=prod").unwrap(), ("env".to_string(), "prod".to_string()));
+    assert_eq!(parse_tag("a=b=c").unwrap(), ("a".to_string(), "b=c".to_string()));
+    assert!(parse_tag("env").is_err());
+  }
+}
@@ -1,15 +1,29 @@
-use std::collections::{HashMap, HashSet};
-
-use anyhow::{Context, Result};
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::Result;
+use k8s_openapi::{
+  api::{core, policy},
+  apimachinery::pkg::{
+    apis::meta::v1::{ObjectMeta, Time},
+    util::intstr::IntOrString,
+  },
+  chrono::{DateTime, Utc},
+  kube_aggregator::pkg::apis::apiregistration::v1::APIService,
+};
 use serde::{Deserialize, Serialize};
 use tabled::{
   settings::{locator::ByColumnName, Disable, Margin, Style},
   Table, Tabled,
 };
+use tracing::warn;
 
 use crate::{
-  finding::{self, Findings},
-  k8s::resources::{self, Resource},
+  finding::{self, Findings, ToEntry},
+  k8s::{
+    resources::{self, Resource},
+    storage::NO_STORAGE_CLASS,
+  },
+  output::tabled_vec_to_string,
   version,
 };
 
@@ -22,6 +36,8 @@ pub struct VersionSkew {
   #[tabled(inline)]
   pub finding: finding::Finding,
   pub name: String,
+  /// The nodegroup, Fargate profile, or Karpenter node pool the node belongs to
+  pub nodegroup: String,
   #[tabled(skip)]
   pub kubelet_version: String,
   #[tabled(rename = "NODE")]
@@ -30,6 +46,9 @@ pub struct VersionSkew {
   pub control_plane_version: String,
   #[tabled(rename = "SKEW")]
   pub version_skew: String,
+  /// The skew between the node and the control plane once the control plane has been upgraded
+  #[tabled(rename = "TARGET SKEW")]
+  pub target_version_skew: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Tabled)]
@@ -40,6 +59,39 @@ pub struct VersionSkewSummary {
   pub quantity: i32,
 }
 
+impl ToEntry for VersionSkew {
+  fn to_entry(&self) -> finding::Entry {
+    finding::Entry {
+      api_version: Some("v1".to_string()),
+      ..finding::Entry::new(&self.finding, "Node", &self.name)
+    }
+  }
+}
+
+/// Aggregate the version skew findings per nodegroup, node version, and remediation
+fn version_skew_summary(findings: &[VersionSkew]) -> Table {
+  let mut summary: BTreeMap<(String, String, String), VersionSkewSummary> = BTreeMap::new();
+  for node in findings {
+    let key = (
+      node.nodegroup.to_owned(),
+      node.kubernetes_version.to_owned(),
+      node.finding.remediation.to_string(),
+    );
+
+    summary
+      .entry(key)
+      .and_modify(|summary| summary.quantity += 1)
+      .or_insert_with(|| VersionSkewSummary {
+        version_skew: node.clone(),
+        quantity: 1,
+      });
+  }
+
+  let mut table = Table::new(summary.into_values());
+  table.with(Disable::column(ByColumnName::new("NAME")));
+  table
+}
+
 impl Findings for Vec<VersionSkew> {
   fn to_markdown_table(&self, leading_whitespace: &str) -> Result<String> {
     if self.is_empty() {
@@ -48,34 +100,9 @@ impl Findings for Vec<VersionSkew> {
       ));
     }
 
-    let mut summary: HashMap<(String, String, String, String, String), VersionSkewSummary> = HashMap::new();
-    for node in self {
-      let key = (
-        node.finding.code.to_string(),
-        node.finding.symbol.to_owned(),
-        node.finding.remediation.to_string(),
-        node.kubernetes_version.to_owned(),
-        node.control_plane_version.to_owned(),
-      );
-
-      if let Some(summary) = summary.get_mut(&key) {
-        summary.quantity += 1;
-      } else {
-        summary.insert(
-          key,
-          VersionSkewSummary {
-            version_skew: node.clone(),
-            quantity: 1,
-          },
-        );
-      }
-    }
-
-    let mut summary_tbl = Table::new(summary);
+    let mut summary_tbl = version_skew_summary(self);
     summary_tbl
       .with(Margin::new(1, 0, 0, 0).fill('\t', 'x', 'x', 'x'))
-      .with(Disable::column(ByColumnName::new("String")))
-      .with(Disable::column(ByColumnName::new("NAME")))
       .with(Style::markdown());
 
     let mut table = Table::new(self);
@@ -92,30 +119,64 @@ impl Findings for Vec<VersionSkew> {
       return Ok("".to_owned());
     }
 
+    let mut summary_tbl = version_skew_summary(self);
+    summary_tbl.with(Style::sharp());
+
     let mut table = Table::new(self);
     table.with(Style::sharp());
 
-    Ok(format!("{table}\n"))
+    Ok(format!("{summary_tbl}\n{table}\n"))
+  }
+}
+
+/// Returns the nodegroup, Fargate profile, or Karpenter node pool the node belongs to, based on its labels
+pub(crate) fn get_nodegroup(node: &resources::Node) -> String {
+  if node.name.starts_with("fargate-") {
+    return "fargate".to_string();
   }
+
+  let labels = match &node.labels {
+    Some(labels) => labels,
+    None => return "-".to_string(),
+  };
+
+  [
+    "eks.amazonaws.com/nodegroup",
+    "alpha.eksctl.io/nodegroup-name",
+    "karpenter.sh/nodepool",
+    "karpenter.sh/provisioner-name",
+  ]
+  .iter()
+  .find_map(|label| labels.get(*label))
+  .map(|nodegroup| nodegroup.to_owned())
+  .unwrap_or_else(|| "-".to_string())
 }
 
-/// Returns all of the nodes in the cluster
-pub async fn version_skew(nodes: &[resources::Node], cluster_version: &str) -> Result<Vec<VersionSkew>> {
+/// Check the version skew between the control plane and the nodes
+///
+/// The remediation is determined by the version skew policy of the target version, based on the skew
+/// that will exist once the control plane has been upgraded. A node that will remain within the policy is
+/// recommended to be upgraded, whereas a node that will fall outside of the policy is required to be upgraded
+pub async fn version_skew(
+  nodes: &[resources::Node],
+  cluster_version: &version::Version,
+  target_version: &version::Version,
+) -> Result<Vec<VersionSkew>> {
   let mut findings = vec![];
 
   for node in nodes {
-    let control_plane_minor_version = version::parse_minor(cluster_version)?;
-    let version_skew = control_plane_minor_version - node.minor_version;
+    let version_skew = cluster_version.minor_skew(&node.version);
     if version_skew == 0 {
       continue;
     }
-
-    // Prior to upgrade, the node version should not be more than 1 version behind
-    // the control plane version. If it is, the node must be upgraded before
-    // attempting the cluster upgrade
-    let mut remediation = match version_skew {
-      1 => finding::Remediation::Recommended,
-      _ => finding::Remediation::Required,
+    let target_version_skew = target_version.minor_skew(&node.version);
+
+    // Nodes newer than the control plane, or that will fall outside of the skew policy of the
+    // target version once the control plane is upgraded, must be upgraded first
+    let mut remediation = if version_skew < 0 || target_version_skew > target_version.max_kubelet_skew() {
+      finding::Remediation::Required
+    } else {
+      finding::Remediation::Recommended
     };
 
     if let Some(labels) = &node.labels {
@@ -141,10 +202,12 @@ pub async fn version_skew(nodes: &[resources::Node], cluster_version: &str) -> R
     let node = VersionSkew {
       finding,
       name: node.name.to_owned(),
+      nodegroup: get_nodegroup(node),
       kubelet_version: node.kubelet_version.to_owned(),
-      kubernetes_version: format!("v{}", version::normalize(&node.kubelet_version).unwrap()),
+      kubernetes_version: format!("v{}", node.version),
       control_plane_version: format!("v{cluster_version}"),
-      version_skew: format!("+{version_skew}"),
+      version_skew: format!("{version_skew:+}"),
+      target_version_skew: format!("{target_version_skew:+}"),
     };
 
     findings.push(node)
@@ -164,6 +227,12 @@ pub struct MinReplicas {
   pub replicas: i32,
 }
 
+impl ToEntry for MinReplicas {
+  fn to_entry(&self) -> finding::Entry {
+    self.resource.to_entry(&self.finding)
+  }
+}
+
 impl Findings for Vec<MinReplicas> {
   fn to_markdown_table(&self, leading_whitespace: &str) -> Result<String> {
     if self.is_empty() {
@@ -204,6 +273,12 @@ pub struct MinReadySeconds {
   pub seconds: i32,
 }
 
+impl ToEntry for MinReadySeconds {
+  fn to_entry(&self) -> finding::Entry {
+    self.resource.to_entry(&self.finding)
+  }
+}
+
 impl Findings for Vec<MinReadySeconds> {
   fn to_markdown_table(&self, leading_whitespace: &str) -> Result<String> {
     if self.is_empty() {
@@ -240,8 +315,51 @@ pub struct PodDisruptionBudget {
   pub finding: finding::Finding,
   #[tabled(inline)]
   pub resource: Resource,
-  // Has pod associated pod disruption budget
-  // TODO - more relevant information than just present?
+  /// Names of the podDisruptionBudgets that cover the workload, if any
+  #[tabled(rename = "PDBS", display_with = "display_pdbs")]
+  pub pdbs: Vec<String>,
+}
+
+fn display_pdbs(pdbs: &[String]) -> String {
+  match pdbs.is_empty() {
+    true => "-".to_string(),
+    false => tabled_vec_to_string(pdbs),
+  }
+}
+
+impl ToEntry for PodDisruptionBudget {
+  fn to_entry(&self) -> finding::Entry {
+    self.resource.to_entry(&self.finding)
+  }
+}
+
+impl Findings for Vec<PodDisruptionBudget> {
+  fn to_markdown_table(&self, leading_whitespace: &str) -> Result<String> {
+    if self.is_empty() {
+      return Ok(format!(
+        "{leading_whitespace}✅ - All relevant Kubernetes workloads are covered by a podDisruptionBudget with minAvailable or maxUnavailable set"
+      ));
+    }
+
+    let mut table = Table::new(self);
+    table
+      .with(Disable::column(ByColumnName::new("CHECK")))
+      .with(Margin::new(1, 0, 0, 0).fill('\t', 'x', 'x', 'x'))
+      .with(Style::markdown());
+
+    Ok(format!("{table}\n"))
+  }
+
+  fn to_stdout_table(&self) -> Result<String> {
+    if self.is_empty() {
+      return Ok("".to_owned());
+    }
+
+    let mut table = Table::new(self);
+    table.with(Style::sharp());
+
+    Ok(format!("{table}\n"))
+  }
 }
 
 #[derive(Debug, Serialize, Deserialize, Tabled)]
@@ -254,13 +372,20 @@ pub struct PodTopologyDistribution {
 
   pub anti_affinity: bool,
   pub topology_spread_constraints: bool,
+  pub issue: String,
+}
+
+impl ToEntry for PodTopologyDistribution {
+  fn to_entry(&self) -> finding::Entry {
+    self.resource.to_entry(&self.finding)
+  }
 }
 
 impl Findings for Vec<PodTopologyDistribution> {
   fn to_markdown_table(&self, leading_whitespace: &str) -> Result<String> {
     if self.is_empty() {
       return Ok(format!(
-        "{leading_whitespace}✅ - All relevant Kubernetes workloads have either podAntiAffinity or topologySpreadConstraints set"
+        "{leading_whitespace}✅ - All relevant Kubernetes workloads are spread across availability zones"
       ));
     }
 
@@ -293,15 +418,22 @@ pub struct Probe {
 
   #[tabled(inline)]
   pub resource: Resource,
-  #[tabled(rename = "READINESS PROBE")]
-  pub readiness_probe: bool,
+  /// Name of the container in the pod template with the probe issue
+  pub container: String,
+  pub issue: String,
+}
+
+impl ToEntry for Probe {
+  fn to_entry(&self) -> finding::Entry {
+    self.resource.to_entry(&self.finding)
+  }
 }
 
 impl Findings for Vec<Probe> {
   fn to_markdown_table(&self, leading_whitespace: &str) -> Result<String> {
     if self.is_empty() {
       return Ok(format!(
-        "{leading_whitespace}✅ - All relevant Kubernetes workloads have a readiness probe configured"
+        "{leading_whitespace}✅ - All relevant Kubernetes workloads have their container probes configured appropriately"
       ));
     }
 
@@ -338,6 +470,12 @@ pub struct TerminationGracePeriod {
   pub termination_grace_period: i64,
 }
 
+impl ToEntry for TerminationGracePeriod {
+  fn to_entry(&self) -> finding::Entry {
+    self.resource.to_entry(&self.finding)
+  }
+}
+
 impl Findings for Vec<TerminationGracePeriod> {
   fn to_markdown_table(&self, leading_whitespace: &str) -> Result<String> {
     if self.is_empty() {
@@ -379,6 +517,12 @@ pub struct DockerSocket {
   pub docker_socket: bool,
 }
 
+impl ToEntry for DockerSocket {
+  fn to_entry(&self) -> finding::Entry {
+    self.resource.to_entry(&self.finding)
+  }
+}
+
 impl Findings for Vec<DockerSocket> {
   fn to_markdown_table(&self, leading_whitespace: &str) -> Result<String> {
     if self.is_empty() {
@@ -418,6 +562,12 @@ pub struct PodSecurityPolicy {
   pub resource: Resource,
 }
 
+impl ToEntry for PodSecurityPolicy {
+  fn to_entry(&self) -> finding::Entry {
+    self.resource.to_entry(&self.finding)
+  }
+}
+
 impl Findings for Vec<PodSecurityPolicy> {
   fn to_markdown_table(&self, leading_whitespace: &str) -> Result<String> {
     if self.is_empty() {
@@ -466,9 +616,7 @@ pub async fn kube_proxy_version_skew(
 ) -> Result<Vec<KubeProxyVersionSkew>> {
   let kube_proxy = match resources
     .iter()
-    .filter(|r| r.metadata.kind == resources::Kind::DaemonSet && r.metadata.name == "kube-proxy")
-    .collect::<Vec<_>>()
-    .get(0)
+    .find(|r| r.metadata.kind == resources::Kind::DaemonSet && r.metadata.name == "kube-proxy")
   {
     Some(k) => k.to_owned(),
     None => {
@@ -477,25 +625,27 @@ pub async fn kube_proxy_version_skew(
     }
   };
 
-  let ptmpl = kube_proxy.spec.template.as_ref().unwrap();
-  let pspec = ptmpl.spec.as_ref().unwrap();
-  let kproxy_minor_version = pspec
-    .containers
-    .iter()
-    .map(|container| {
-      // TODO - this seems brittle
-      let image = container.image.as_ref().unwrap().split(':').collect::<Vec<_>>()[1];
-      version::parse_minor(image).unwrap()
-    })
-    .next()
-    .context("Unable to find image version for kube-proxy")?;
+  let image = kube_proxy
+    .spec
+    .template
+    .as_ref()
+    .and_then(|template| template.spec.as_ref())
+    .and_then(|spec| spec.containers.iter().find_map(|container| container.image.to_owned()))
+    .unwrap_or_default();
+  let kproxy_version: version::Version = match image.parse() {
+    Ok(version) => version,
+    Err(err) => {
+      warn!("Unable to determine the kube-proxy version: {err}");
+      return Ok(vec![]);
+    }
+  };
 
   let findings = nodes
     .iter()
-    .map(|node| node.minor_version)
+    .map(|node| node.version)
     .collect::<HashSet<_>>()
     .into_iter()
-    .filter(|node_ver| node_ver != &kproxy_minor_version)
+    .filter(|node_ver| node_ver != &kproxy_version)
     .map(|node_ver| {
       let remediation = finding::Remediation::Required;
       let finding = finding::Finding {
@@ -506,9 +656,9 @@ pub async fn kube_proxy_version_skew(
 
       KubeProxyVersionSkew {
         finding,
-        kubelet_version: format!("v1.{node_ver}"),
-        kube_proxy_version: format!("v1.{kproxy_minor_version}"),
-        version_skew: format!("{}", kproxy_minor_version - node_ver),
+        kubelet_version: format!("v{node_ver}"),
+        kube_proxy_version: format!("v{kproxy_version}"),
+        version_skew: format!("{}", kproxy_version.minor_skew(&node_ver)),
       }
     })
     .collect();
@@ -516,6 +666,16 @@ pub async fn kube_proxy_version_skew(
   Ok(findings)
 }
 
+impl ToEntry for KubeProxyVersionSkew {
+  fn to_entry(&self) -> finding::Entry {
+    finding::Entry {
+      api_version: Some(resources::Kind::DaemonSet.api_version().to_string()),
+      namespace: Some("kube-system".to_string()),
+      ..finding::Entry::new(&self.finding, &resources::Kind::DaemonSet.to_string(), "kube-proxy")
+    }
+  }
+}
+
 impl Findings for Vec<KubeProxyVersionSkew> {
   fn to_markdown_table(&self, leading_whitespace: &str) -> Result<String> {
     if self.is_empty() {
@@ -545,6 +705,299 @@ impl Findings for Vec<KubeProxyVersionSkew> {
   }
 }
 
+fn display_replacement(replacement: &Option<String>) -> String {
+  replacement.to_owned().unwrap_or_else(|| "-".to_string())
+}
+
+/// Usage of a Kubernetes API that is removed at or before the target version
+#[derive(Debug, Serialize, Deserialize, Tabled)]
+#[tabled(rename_all = "UpperCase")]
+pub struct RemovedApi {
+  #[tabled(inline)]
+  pub finding: finding::Finding,
+  #[tabled(rename = "API VERSION")]
+  pub api_version: String,
+  pub kind: String,
+  pub namespace: String,
+  /// Name of the object, or `*` for requests recorded by the API server metrics
+  pub name: String,
+  #[tabled(rename = "REMOVED IN")]
+  pub removed_in: version::Version,
+  #[tabled(display_with = "display_replacement")]
+  pub replacement: Option<String>,
+  /// Either `object` for objects last written through the removed API, `request` for requests
+  /// to the removed API recorded by the API server, or the path of the manifest scanned
+  pub source: String,
+}
+
+impl ToEntry for RemovedApi {
+  fn to_entry(&self) -> finding::Entry {
+    finding::Entry {
+      api_version: Some(self.api_version.to_owned()),
+      namespace: match self.namespace.as_str() {
+        "" => None,
+        namespace => Some(namespace.to_owned()),
+      },
+      ..finding::Entry::new(&self.finding, &self.kind, &self.name)
+    }
+  }
+}
+
+impl Findings for Vec<RemovedApi> {
+  fn to_markdown_table(&self, leading_whitespace: &str) -> Result<String> {
+    if self.is_empty() {
+      return Ok(format!(
+        "{leading_whitespace}✅ - No removed APIs were found in use within the cluster"
+      ));
+    }
+
+    let mut table = Table::new(self);
+    table
+      .with(Disable::column(ByColumnName::new("CHECK")))
+      .with(Margin::new(1, 0, 0, 0).fill('\t', 'x', 'x', 'x'))
+      .with(Style::markdown());
+
+    Ok(format!("{table}\n"))
+  }
+
+  fn to_stdout_table(&self) -> Result<String> {
+    if self.is_empty() {
+      return Ok("".to_owned());
+    }
+
+    let mut table = Table::new(self);
+    table.with(Style::sharp());
+
+    Ok(format!("{table}\n"))
+  }
+}
+
+/// Helm release revision whose stored manifest contains APIs removed at or before the target version
+#[derive(Debug, Serialize, Deserialize, Tabled)]
+#[tabled(rename_all = "UpperCase")]
+pub struct HelmRelease {
+  #[tabled(inline)]
+  pub finding: finding::Finding,
+  pub release: String,
+  pub namespace: String,
+  pub revision: u32,
+  pub status: String,
+  /// Removed APIs in the release manifest, in the form of `<apiVersion> <kind>/<name>`
+  #[tabled(rename = "REMOVED APIS", display_with = "tabled_vec_to_string")]
+  pub removed_apis: Vec<String>,
+}
+
+impl ToEntry for HelmRelease {
+  /// Findings are reported on the Secret that stores the release revision
+  fn to_entry(&self) -> finding::Entry {
+    finding::Entry {
+      api_version: Some("v1".to_string()),
+      namespace: Some(self.namespace.to_owned()),
+      ..finding::Entry::new(
+        &self.finding,
+        "Secret",
+        &format!("sh.helm.release.v1.{}.v{}", self.release, self.revision),
+      )
+    }
+  }
+}
+
+impl Findings for Vec<HelmRelease> {
+  fn to_markdown_table(&self, leading_whitespace: &str) -> Result<String> {
+    if self.is_empty() {
+      return Ok(format!(
+        "{leading_whitespace}✅ - No removed APIs were found in the Helm release history"
+      ));
+    }
+
+    let mut table = Table::new(self);
+    table
+      .with(Disable::column(ByColumnName::new("CHECK")))
+      .with(Margin::new(1, 0, 0, 0).fill('\t', 'x', 'x', 'x'))
+      .with(Style::markdown());
+
+    Ok(format!("{table}\n"))
+  }
+
+  fn to_stdout_table(&self) -> Result<String> {
+    if self.is_empty() {
+      return Ok("".to_owned());
+    }
+
+    let mut table = Table::new(self);
+    table.with(Style::sharp());
+
+    Ok(format!("{table}\n"))
+  }
+}
+
+/// PodDisruptionBudget that prevents the pods it selects from being evicted, blocking node drains
+#[derive(Debug, Serialize, Deserialize, Tabled)]
+#[tabled(rename_all = "UpperCase")]
+pub struct BlockingPodDisruptionBudget {
+  #[tabled(inline)]
+  pub finding: finding::Finding,
+  pub name: String,
+  pub namespace: String,
+  #[tabled(rename = "MIN AVAILABLE")]
+  pub min_available: String,
+  #[tabled(rename = "MAX UNAVAILABLE")]
+  pub max_unavailable: String,
+  /// Number of pods expected to be selected, as reported by the disruption controller
+  #[tabled(rename = "EXPECTED")]
+  pub expected_pods: i32,
+  /// Number of healthy pods currently selected, as reported by the disruption controller
+  #[tabled(rename = "HEALTHY")]
+  pub current_healthy: i32,
+  pub reason: String,
+}
+
+impl ToEntry for BlockingPodDisruptionBudget {
+  fn to_entry(&self) -> finding::Entry {
+    finding::Entry {
+      api_version: Some("policy/v1".to_string()),
+      namespace: Some(self.namespace.to_owned()),
+      ..finding::Entry::new(&self.finding, "PodDisruptionBudget", &self.name)
+    }
+  }
+}
+
+impl Findings for Vec<BlockingPodDisruptionBudget> {
+  fn to_markdown_table(&self, leading_whitespace: &str) -> Result<String> {
+    if self.is_empty() {
+      return Ok(format!(
+        "{leading_whitespace}✅ - No podDisruptionBudgets were found that would block node drains"
+      ));
+    }
+
+    let mut table = Table::new(self);
+    table
+      .with(Disable::column(ByColumnName::new("CHECK")))
+      .with(Margin::new(1, 0, 0, 0).fill('\t', 'x', 'x', 'x'))
+      .with(Style::markdown());
+
+    Ok(format!("{table}\n"))
+  }
+
+  fn to_stdout_table(&self) -> Result<String> {
+    if self.is_empty() {
+      return Ok("".to_owned());
+    }
+
+    let mut table = Table::new(self);
+    table.with(Style::sharp());
+
+    Ok(format!("{table}\n"))
+  }
+}
+
+/// Resolve an integer or a percentage of the total, rounding up as the disruption controller does
+fn scaled_value(value: &IntOrString, total: i32) -> Option<i32> {
+  match value {
+    IntOrString::Int(value) => Some(*value),
+    IntOrString::String(value) => {
+      let percent: i32 = value.strip_suffix('%')?.parse().ok()?;
+      Some((percent * total + 99) / 100)
+    }
+  }
+}
+
+fn display_int_or_string(value: &Option<IntOrString>) -> String {
+  match value {
+    Some(IntOrString::Int(value)) => value.to_string(),
+    Some(IntOrString::String(value)) => value.to_owned(),
+    None => "-".to_string(),
+  }
+}
+
+/// Returns true if the pod has completed (`Succeeded` or `Failed`) and no longer runs on its node
+fn is_completed(pod: &core::v1::Pod) -> bool {
+  let phase = pod.status.as_ref().and_then(|status| status.phase.as_deref());
+  matches!(phase, Some("Succeeded") | Some("Failed"))
+}
+
+/// K8S014 - check for podDisruptionBudgets that prevent the pods they select from being evicted
+///
+/// The pods are used to determine the podDisruptionBudgets that select no pods, or that select the same
+/// pods as another podDisruptionBudget (the eviction API rejects pods with more than one podDisruptionBudget);
+/// completed pods are not evicted and are therefore ignored. `minAvailable` is compared against the number of
+/// healthy pods reported in the status by the disruption controller. When no disruptions are allowed even though
+/// the spec permits them (i.e. - during a rollout, or while a pod is not ready), the state is temporary and is
+/// therefore only recommended to be resolved before the upgrade
+pub fn blocking_pod_disruption_budgets(
+  pdbs: &[policy::v1::PodDisruptionBudget],
+  pods: &[core::v1::Pod],
+) -> Vec<BlockingPodDisruptionBudget> {
+  let namespace = |metadata: &ObjectMeta| metadata.namespace.to_owned().unwrap_or_default();
+  let selects = |pdb: &policy::v1::PodDisruptionBudget, pod: &core::v1::Pod| {
+    let labels = pod.metadata.labels.to_owned().unwrap_or_default();
+    namespace(&pdb.metadata) == namespace(&pod.metadata)
+      && pdb
+        .spec
+        .as_ref()
+        .and_then(|spec| spec.selector.as_ref())
+        .map(|selector| resources::selector_matches(selector, &labels))
+        .unwrap_or(false)
+  };
+  let pods: Vec<&core::v1::Pod> = pods.iter().filter(|pod| !is_completed(pod)).collect();
+
+  pdbs
+    .iter()
+    .filter_map(|pdb| {
+      let spec = pdb.spec.to_owned().unwrap_or_default();
+      let status = pdb.status.as_ref();
+      let selected: Vec<&&core::v1::Pod> = pods.iter().filter(|pod| selects(pdb, pod)).collect();
+      let expected_pods = status
+        .map(|status| status.expected_pods)
+        .unwrap_or(selected.len() as i32);
+      let current_healthy = status
+        .map(|status| status.current_healthy)
+        .unwrap_or(selected.len() as i32);
+
+      let overlapping = selected
+        .iter()
+        .any(|pod| pdbs.iter().filter(|other| selects(other, pod)).count() > 1);
+      // Percentages are of the expected pods, as resolved by the disruption controller
+      let max_unavailable = spec
+        .max_unavailable
+        .as_ref()
+        .and_then(|v| scaled_value(v, expected_pods));
+      let min_available = spec.min_available.as_ref().and_then(|v| scaled_value(v, expected_pods));
+
+      let (remediation, reason) = if overlapping {
+        (finding::Remediation::Required, "Pods are selected by multiple PDBs")
+      } else if selected.is_empty() {
+        (finding::Remediation::Recommended, "Selects no pods")
+      } else if max_unavailable == Some(0) {
+        (finding::Remediation::Required, "maxUnavailable is 0")
+      } else if min_available.map(|min| min >= current_healthy).unwrap_or(false) {
+        (finding::Remediation::Required, "minAvailable >= healthy pods")
+      } else if status.map(|status| status.disruptions_allowed == 0).unwrap_or(false) {
+        (finding::Remediation::Recommended, "No disruptions currently allowed")
+      } else {
+        return None;
+      };
+
+      let finding = finding::Finding {
+        code: finding::Code::K8S014,
+        symbol: remediation.symbol(),
+        remediation,
+      };
+
+      Some(BlockingPodDisruptionBudget {
+        finding,
+        name: pdb.metadata.name.to_owned().unwrap_or_default(),
+        namespace: namespace(&pdb.metadata),
+        min_available: display_int_or_string(&spec.min_available),
+        max_unavailable: display_int_or_string(&spec.max_unavailable),
+        expected_pods,
+        current_healthy,
+        reason: reason.to_string(),
+      })
+    })
+    .collect()
+}
+
 pub trait K8sFindings {
   fn get_resource(&self) -> Resource;
 
@@ -554,20 +1007,1339 @@ pub trait K8sFindings {
   /// K8S003 - check if resources contain minReadySeconds > 0
   fn min_ready_seconds(&self) -> Option<MinReadySeconds>;
 
-  // /// K8S004 - check if resources have associated podDisruptionBudgets
-  // fn pod_disruption_budget(&self) -> Option<PodDisruptionBudget>;
+  /// K8S004 - check if resources are covered by a podDisruptionBudget with minAvailable or maxUnavailable set
+  fn pod_disruption_budget(&self, pdbs: &[policy::v1::PodDisruptionBudget]) -> Option<PodDisruptionBudget>;
 
-  /// K8S005 - check if resources have podAntiAffinity or topologySpreadConstraints
-  fn pod_topology_distribution(&self) -> Option<PodTopologyDistribution>;
+  /// K8S005 - check if resources are spread across availability zones with podAntiAffinity or topologySpreadConstraints
+  ///
+  /// `node_count` is the number of nodes in the cluster, when known, used to recommend topologySpreadConstraints
+  /// over required podAntiAffinity in large clusters
+  fn pod_topology_distribution(&self, node_count: Option<usize>) -> Option<PodTopologyDistribution>;
 
-  /// K8S006 - check if resources have readinessProbe
-  fn readiness_probe(&self) -> Option<Probe>;
+  /// K8S006 - check if the containers of resources have a readinessProbe, and a livenessProbe that
+  /// does not restart pods that are only temporarily unavailable
+  fn probes(&self) -> Vec<Probe>;
 
   /// K8S007 - check if StatefulSets have terminationGracePeriodSeconds == 0
   fn termination_grace_period(&self) -> Option<TerminationGracePeriod>;
 
   /// K8S008 - check if resources use the Docker socket
-  fn docker_socket(&self, target_version: &str) -> Option<DockerSocket>;
+  fn docker_socket(&self, target_version: &version::Version) -> Option<DockerSocket>;
 
   // K8S009 - pod security policies (separate from workload resources)
+
+  // K8S012 - removed APIs (separate from workload resources)
+
+  // K8S013 - Helm release history (separate from workload resources)
+}
+
+/// In-tree Amazon EBS StorageClass or PersistentVolume that relies on the Amazon EBS CSI driver after migration
+#[derive(Debug, Serialize, Deserialize, Tabled)]
+#[tabled(rename_all = "UpperCase")]
+pub struct EbsCsiMigration {
+  #[tabled(inline)]
+  pub finding: finding::Finding,
+  pub kind: String,
+  pub name: String,
+  /// Installation and health of the Amazon EBS CSI driver
+  #[tabled(rename = "CSI DRIVER")]
+  pub csi_driver: String,
+  /// IAM role used by the Amazon EBS CSI driver
+  #[tabled(rename = "IAM ROLE")]
+  pub iam_role: String,
+}
+
+impl ToEntry for EbsCsiMigration {
+  fn to_entry(&self) -> finding::Entry {
+    let api_version = match self.kind.as_str() {
+      "StorageClass" => "storage.k8s.io/v1",
+      _ => "v1",
+    };
+
+    finding::Entry {
+      api_version: Some(api_version.to_string()),
+      ..finding::Entry::new(&self.finding, &self.kind, &self.name)
+    }
+  }
+}
+
+impl Findings for Vec<EbsCsiMigration> {
+  fn to_markdown_table(&self, leading_whitespace: &str) -> Result<String> {
+    if self.is_empty() {
+      return Ok(format!(
+        "{leading_whitespace}✅ - No in-tree Amazon EBS storageClasses or persistentVolumes were found"
+      ));
+    }
+
+    let mut table = Table::new(self);
+    table
+      .with(Disable::column(ByColumnName::new("CHECK")))
+      .with(Margin::new(1, 0, 0, 0).fill('\t', 'x', 'x', 'x'))
+      .with(Style::markdown());
+
+    Ok(format!("{table}\n"))
+  }
+
+  fn to_stdout_table(&self) -> Result<String> {
+    if self.is_empty() {
+      return Ok("".to_owned());
+    }
+
+    let mut table = Table::new(self);
+    table.with(Style::sharp());
+
+    Ok(format!("{table}\n"))
+  }
+}
+
+/// StorageClass provisioning `gp2` Amazon EBS volumes, and its volumes in a namespace
+#[derive(Debug, Serialize, Deserialize, Tabled)]
+#[tabled(rename_all = "UpperCase")]
+pub struct Gp2Volume {
+  #[tabled(inline)]
+  pub finding: finding::Finding,
+  #[tabled(rename = "STORAGE CLASS")]
+  pub storage_class: String,
+  /// The StorageClass is the default StorageClass of the cluster
+  pub default: bool,
+  /// Namespace of the claims bound to the volumes
+  pub namespace: String,
+  pub volumes: usize,
+  /// Aggregate capacity of the volumes
+  pub capacity: String,
+}
+
+impl ToEntry for Gp2Volume {
+  fn to_entry(&self) -> finding::Entry {
+    finding::Entry {
+      // Volumes without an existing StorageClass are not reported on a Kubernetes resource
+      api_version: (self.storage_class != NO_STORAGE_CLASS).then(|| "storage.k8s.io/v1".to_string()),
+      namespace: (!self.namespace.is_empty()).then(|| self.namespace.to_owned()),
+      ..finding::Entry::new(&self.finding, "StorageClass", &self.storage_class)
+    }
+  }
+}
+
+impl Findings for Vec<Gp2Volume> {
+  fn to_markdown_table(&self, leading_whitespace: &str) -> Result<String> {
+    if self.is_empty() {
+      return Ok(format!(
+        "{leading_whitespace}✅ - No storageClasses were found that provision gp2 Amazon EBS volumes"
+      ));
+    }
+
+    let mut table = Table::new(self);
+    table
+      .with(Disable::column(ByColumnName::new("CHECK")))
+      .with(Margin::new(1, 0, 0, 0).fill('\t', 'x', 'x', 'x'))
+      .with(Style::markdown());
+
+    Ok(format!("{table}\n"))
+  }
+
+  fn to_stdout_table(&self) -> Result<String> {
+    if self.is_empty() {
+      return Ok("".to_owned());
+    }
+
+    let mut table = Table::new(self);
+    table.with(Style::sharp());
+
+    Ok(format!("{table}\n"))
+  }
+}
+
+/// Workload whose running replicas are all placed on a single node, nodegroup, or availability zone
+#[derive(Debug, Serialize, Deserialize, Tabled)]
+#[tabled(rename_all = "UpperCase")]
+pub struct PodPlacement {
+  #[tabled(inline)]
+  pub finding: finding::Finding,
+  pub name: String,
+  pub namespace: String,
+  pub kind: String,
+  #[tabled(skip)]
+  pub api_version: String,
+  /// Number of running replicas
+  pub replicas: usize,
+  pub nodes: usize,
+  pub nodegroups: usize,
+  pub zones: usize,
+  pub issue: String,
+}
+
+impl ToEntry for PodPlacement {
+  fn to_entry(&self) -> finding::Entry {
+    finding::Entry {
+      api_version: Some(self.api_version.to_owned()),
+      namespace: Some(self.namespace.to_owned()),
+      ..finding::Entry::new(&self.finding, &self.kind, &self.name)
+    }
+  }
+}
+
+impl Findings for Vec<PodPlacement> {
+  fn to_markdown_table(&self, leading_whitespace: &str) -> Result<String> {
+    if self.is_empty() {
+      return Ok(format!(
+        "{leading_whitespace}✅ - The running replicas of all relevant Kubernetes workloads are spread across nodes, nodegroups, and zones"
+      ));
+    }
+
+    let mut table = Table::new(self);
+    table
+      .with(Disable::column(ByColumnName::new("CHECK")))
+      .with(Margin::new(1, 0, 0, 0).fill('\t', 'x', 'x', 'x'))
+      .with(Style::markdown());
+
+    Ok(format!("{table}\n"))
+  }
+
+  fn to_stdout_table(&self) -> Result<String> {
+    if self.is_empty() {
+      return Ok("".to_owned());
+    }
+
+    let mut table = Table::new(self);
+    table.with(Style::sharp());
+
+    Ok(format!("{table}\n"))
+  }
+}
+
+/// K8S016 - check where the running replicas of each workload are actually placed
+///
+/// Pods are resolved to their top-level controller and grouped by the node, nodegroup, and zone they run on.
+/// Workloads with more than one running replica that are all placed on a single node (or nodegroup) will be
+/// fully unavailable when that node (or nodegroup) is replaced, regardless of the placement rules in the spec.
+/// Pods of DaemonSets, Jobs, and CronJobs, as well as static pods, are not considered
+pub fn pod_placement(
+  pods: &[core::v1::Pod],
+  nodes: &[resources::Node],
+  owners: &resources::Owners,
+) -> Vec<PodPlacement> {
+  let nodes: BTreeMap<&str, &resources::Node> = nodes.iter().map(|node| (node.name.as_str(), node)).collect();
+
+  let mut workloads: BTreeMap<(String, String, String, String), Vec<&resources::Node>> = BTreeMap::new();
+  for pod in pods {
+    let running = pod.status.as_ref().and_then(|status| status.phase.as_deref()) == Some("Running");
+    let node = pod
+      .spec
+      .as_ref()
+      .and_then(|spec| spec.node_name.as_deref())
+      .and_then(|name| nodes.get(name));
+    let (node, controller) = match (
+      running,
+      node,
+      resources::get_top_level_controller(&pod.metadata, owners),
+    ) {
+      (true, Some(node), Some(controller)) => (node, controller),
+      _ => continue,
+    };
+    if ["DaemonSet", "Job", "CronJob", "Node"].contains(&controller.kind.as_str()) {
+      continue;
+    }
+
+    workloads
+      .entry((
+        pod.metadata.namespace.to_owned().unwrap_or_default(),
+        controller.kind,
+        controller.name,
+        controller.api_version,
+      ))
+      .or_default()
+      .push(node);
+  }
+
+  workloads
+    .into_iter()
+    .filter(|(_, nodes)| nodes.len() > 1)
+    .filter_map(|((namespace, kind, name, api_version), nodes)| {
+      let node_names: HashSet<&str> = nodes.iter().map(|node| node.name.as_str()).collect();
+      let nodegroups: HashSet<String> = nodes.iter().map(|node| get_nodegroup(node)).collect();
+      let zones: HashSet<Option<&String>> = nodes
+        .iter()
+        .map(|node| {
+          let labels = node.labels.as_ref()?;
+          resources::ZONE_TOPOLOGY_KEYS.iter().find_map(|key| labels.get(*key))
+        })
+        .collect();
+
+      // Nodegroups and zones that cannot be determined from the node labels are not evaluated
+      let single = |values: &HashSet<String>| {
+        values
+          .iter()
+          .next()
+          .filter(|value| values.len() == 1 && value.as_str() != "-")
+          .cloned()
+      };
+      let zone = match zones.iter().next() {
+        Some(Some(zone)) if zones.len() == 1 => Some(zone.to_string()),
+        _ => None,
+      };
+
+      let (remediation, issue) = if node_names.len() == 1 {
+        (
+          finding::Remediation::Required,
+          format!("All replicas on node {}", nodes[0].name),
+        )
+      } else if let Some(nodegroup) = single(&nodegroups) {
+        (
+          finding::Remediation::Recommended,
+          format!("All replicas in nodegroup {nodegroup}"),
+        )
+      } else if let Some(zone) = zone {
+        (
+          finding::Remediation::Recommended,
+          format!("All replicas in zone {zone}"),
+        )
+      } else {
+        return None;
+      };
+
+      Some(PodPlacement {
+        finding: finding::Finding {
+          code: finding::Code::K8S016,
+          symbol: remediation.symbol(),
+          remediation,
+        },
+        name,
+        namespace,
+        kind,
+        api_version,
+        replicas: nodes.len(),
+        nodes: node_names.len(),
+        nodegroups: nodegroups.len(),
+        zones: zones.len(),
+        issue,
+      })
+    })
+    .collect()
+}
+
+/// Pod that is not managed by a controller
+#[derive(Debug, Serialize, Deserialize, Tabled)]
+#[tabled(rename_all = "UpperCase")]
+pub struct BarePod {
+  #[tabled(inline)]
+  pub finding: finding::Finding,
+  pub name: String,
+  pub namespace: String,
+  /// Node the pod is running on
+  pub node: String,
+}
+
+impl ToEntry for BarePod {
+  fn to_entry(&self) -> finding::Entry {
+    finding::Entry {
+      api_version: Some("v1".to_string()),
+      namespace: Some(self.namespace.to_owned()),
+      ..finding::Entry::new(&self.finding, "Pod", &self.name)
+    }
+  }
+}
+
+impl Findings for Vec<BarePod> {
+  fn to_markdown_table(&self, leading_whitespace: &str) -> Result<String> {
+    if self.is_empty() {
+      return Ok(format!(
+        "{leading_whitespace}✅ - No pods were found that are not managed by a controller"
+      ));
+    }
+
+    let mut table = Table::new(self);
+    table
+      .with(Disable::column(ByColumnName::new("CHECK")))
+      .with(Margin::new(1, 0, 0, 0).fill('\t', 'x', 'x', 'x'))
+      .with(Style::markdown());
+
+    Ok(format!("{table}\n"))
+  }
+
+  fn to_stdout_table(&self) -> Result<String> {
+    if self.is_empty() {
+      return Ok("".to_owned());
+    }
+
+    let mut table = Table::new(self);
+    table.with(Style::sharp());
+
+    Ok(format!("{table}\n"))
+  }
+}
+
+/// K8S017 - check for pods that are not managed by a controller
+///
+/// Bare pods are not recreated once they are evicted by a node drain, and are permanently deleted
+/// when their node is replaced. Static pods (managed by the kubelet) and pods that have completed are not reported
+pub fn bare_pods(pods: &[core::v1::Pod]) -> Vec<BarePod> {
+  pods
+    .iter()
+    .filter(|pod| resources::get_controller(&pod.metadata).is_none())
+    .filter(|pod| !is_completed(pod))
+    .map(|pod| {
+      let remediation = finding::Remediation::Required;
+
+      BarePod {
+        finding: finding::Finding {
+          code: finding::Code::K8S017,
+          symbol: remediation.symbol(),
+          remediation,
+        },
+        name: pod.metadata.name.to_owned().unwrap_or_default(),
+        namespace: pod.metadata.namespace.to_owned().unwrap_or_default(),
+        node: pod
+          .spec
+          .as_ref()
+          .and_then(|spec| spec.node_name.to_owned())
+          .unwrap_or_else(|| "-".to_string()),
+      }
+    })
+    .collect()
+}
+
+/// Annotations that prevent a pod from being evicted by the Cluster Autoscaler or Karpenter, and their blocking value
+const DO_NOT_EVICT_ANNOTATIONS: [(&str, &str); 3] = [
+  ("cluster-autoscaler.kubernetes.io/safe-to-evict", "false"),
+  ("karpenter.sh/do-not-evict", "true"),
+  ("karpenter.sh/do-not-disrupt", "true"),
+];
+
+/// Labels and fields that identify a single node
+const HOSTNAME_KEYS: [&str; 2] = ["kubernetes.io/hostname", "metadata.name"];
+
+/// Pod that blocks, or loses data, when its node is drained
+#[derive(Debug, Serialize, Deserialize, Tabled)]
+#[tabled(rename_all = "UpperCase")]
+pub struct EvictionBlocker {
+  #[tabled(inline)]
+  pub finding: finding::Finding,
+  pub name: String,
+  pub namespace: String,
+  /// Node the pod is running on
+  pub node: String,
+  #[tabled(display_with = "tabled_vec_to_string")]
+  pub reasons: Vec<String>,
+}
+
+impl ToEntry for EvictionBlocker {
+  fn to_entry(&self) -> finding::Entry {
+    finding::Entry {
+      api_version: Some("v1".to_string()),
+      namespace: Some(self.namespace.to_owned()),
+      ..finding::Entry::new(&self.finding, "Pod", &self.name)
+    }
+  }
+}
+
+impl Findings for Vec<EvictionBlocker> {
+  fn to_markdown_table(&self, leading_whitespace: &str) -> Result<String> {
+    if self.is_empty() {
+      return Ok(format!(
+        "{leading_whitespace}✅ - No pods were found that would block, or lose data on, node drains"
+      ));
+    }
+
+    let mut table = Table::new(self);
+    table
+      .with(Disable::column(ByColumnName::new("CHECK")))
+      .with(Margin::new(1, 0, 0, 0).fill('\t', 'x', 'x', 'x'))
+      .with(Style::markdown());
+
+    Ok(format!("{table}\n"))
+  }
+
+  fn to_stdout_table(&self) -> Result<String> {
+    if self.is_empty() {
+      return Ok("".to_owned());
+    }
+
+    let mut table = Table::new(self);
+    table.with(Style::sharp());
+
+    Ok(format!("{table}\n"))
+  }
+}
+
+/// Returns the node the pod spec is pinned to through a node selector or required node affinity, if any
+fn pinned_node(spec: &core::v1::PodSpec) -> Option<String> {
+  let selected = spec
+    .node_selector
+    .as_ref()
+    .and_then(|selector| selector.get(HOSTNAME_KEYS[0]).cloned());
+
+  let terms = spec
+    .affinity
+    .as_ref()
+    .and_then(|affinity| affinity.node_affinity.as_ref())
+    .and_then(|affinity| affinity.required_during_scheduling_ignored_during_execution.as_ref())
+    .map(|selector| selector.node_selector_terms.as_slice())
+    .unwrap_or_default();
+  // Each term is evaluated independently (ORed), so each one must be restricted to the same single node
+  let affinity = terms
+    .iter()
+    .map(|term| {
+      term
+        .match_expressions
+        .iter()
+        .chain(term.match_fields.iter())
+        .flatten()
+        .find(|requirement| {
+          HOSTNAME_KEYS.contains(&requirement.key.as_str())
+            && requirement.operator == "In"
+            && requirement.values.as_ref().map(Vec::len) == Some(1)
+        })
+        .and_then(|requirement| requirement.values.as_ref()?.first().cloned())
+    })
+    .collect::<Option<HashSet<String>>>()
+    .filter(|nodes| nodes.len() == 1)
+    .and_then(|nodes| nodes.into_iter().next());
+
+  selected.or(affinity)
+}
+
+/// K8S018 - check for pods that will block, or lose data on, node drains
+///
+/// - Pods annotated to prevent their eviction by the Cluster Autoscaler or Karpenter, or pinned to a single node
+///   (through `nodeName` in the template of their controller, a node selector, or required node affinity), are
+///   required to be remediated since they cannot be moved to a replacement node
+/// - Pods with `emptyDir` or `hostPath` volumes lose the data stored on the node when they are evicted
+///
+/// Pods of DaemonSets, static pods, and pods that have completed are not reported. When the owners of pods
+/// could not be collected, the controllers are determined from the owner references of the pods alone
+pub fn eviction_blockers(
+  pods: &[core::v1::Pod],
+  resources: &[resources::StdResource],
+  owners: Option<&resources::Owners>,
+) -> Vec<EvictionBlocker> {
+  let templates: BTreeMap<(String, String, String), &core::v1::PodSpec> = resources
+    .iter()
+    .filter_map(|resource| {
+      let spec = resource.spec.template.as_ref()?.spec.as_ref()?;
+      let key = (
+        resource.metadata.namespace.to_owned(),
+        resource.metadata.kind.to_string(),
+        resource.metadata.name.to_owned(),
+      );
+      Some((key, spec))
+    })
+    .collect();
+
+  pods
+    .iter()
+    .filter_map(|pod| {
+      if is_completed(pod) {
+        return None;
+      }
+      let controller = match owners {
+        Some(owners) => resources::get_top_level_controller(&pod.metadata, owners),
+        None => resources::get_controller(&pod.metadata).cloned(),
+      };
+      if let Some(controller) = &controller {
+        if ["DaemonSet", "Node"].contains(&controller.kind.as_str()) {
+          return None;
+        }
+      }
+
+      let namespace = pod.metadata.namespace.to_owned().unwrap_or_default();
+      let spec = pod.spec.to_owned().unwrap_or_default();
+      let annotations = pod.metadata.annotations.to_owned().unwrap_or_default();
+      let mut required = Vec::new();
+      let mut recommended = Vec::new();
+
+      for (annotation, value) in DO_NOT_EVICT_ANNOTATIONS {
+        if annotations.get(annotation).map(String::as_str) == Some(value) {
+          required.push(format!("{annotation}: \"{value}\""));
+        }
+      }
+
+      let template_node = controller
+        .and_then(|controller| templates.get(&(namespace.to_owned(), controller.kind, controller.name)))
+        .and_then(|spec| spec.node_name.to_owned());
+      if let Some(node) = template_node {
+        required.push(format!("Pinned to node {node} (nodeName)"));
+      } else if let Some(node) = pinned_node(&spec) {
+        required.push(format!("Pinned to node {node}"));
+      }
+
+      for volume in spec.volumes.iter().flatten() {
+        if volume.empty_dir.is_some() {
+          recommended.push(format!("emptyDir volume {}", volume.name));
+        }
+        if volume.host_path.is_some() {
+          recommended.push(format!("hostPath volume {}", volume.name));
+        }
+      }
+
+      let remediation = match (required.is_empty(), recommended.is_empty()) {
+        (false, _) => finding::Remediation::Required,
+        (true, false) => finding::Remediation::Recommended,
+        (true, true) => return None,
+      };
+
+      Some(EvictionBlocker {
+        finding: finding::Finding {
+          code: finding::Code::K8S018,
+          symbol: remediation.symbol(),
+          remediation,
+        },
+        name: pod.metadata.name.to_owned().unwrap_or_default(),
+        namespace,
+        node: spec.node_name.unwrap_or_else(|| "-".to_string()),
+        reasons: required.into_iter().chain(recommended).collect(),
+      })
+    })
+    .collect()
+}
+
+/// Admission webhook that fails closed, and can prevent objects from being created while nodes are replaced
+#[derive(Debug, Serialize, Deserialize, Tabled)]
+#[tabled(rename_all = "UpperCase")]
+pub struct AdmissionWebhook {
+  #[tabled(inline)]
+  pub finding: finding::Finding,
+  /// Either `ValidatingWebhookConfiguration` or `MutatingWebhookConfiguration`
+  pub kind: String,
+  pub configuration: String,
+  #[tabled(rename = "WEBHOOK")]
+  pub name: String,
+  /// Service backing the webhook, in the form of `<namespace>/<name>`
+  pub service: String,
+  #[tabled(display_with = "tabled_vec_to_string")]
+  pub issues: Vec<String>,
+}
+
+impl ToEntry for AdmissionWebhook {
+  fn to_entry(&self) -> finding::Entry {
+    finding::Entry {
+      api_version: Some("admissionregistration.k8s.io/v1".to_string()),
+      ..finding::Entry::new(&self.finding, &self.kind, &self.configuration)
+    }
+  }
+}
+
+impl Findings for Vec<AdmissionWebhook> {
+  fn to_markdown_table(&self, leading_whitespace: &str) -> Result<String> {
+    if self.is_empty() {
+      return Ok(format!(
+        "{leading_whitespace}✅ - No issues were found with the admission webhooks that fail closed"
+      ));
+    }
+
+    let mut table = Table::new(self);
+    table
+      .with(Disable::column(ByColumnName::new("CHECK")))
+      .with(Margin::new(1, 0, 0, 0).fill('\t', 'x', 'x', 'x'))
+      .with(Style::markdown());
+
+    Ok(format!("{table}\n"))
+  }
+
+  fn to_stdout_table(&self) -> Result<String> {
+    if self.is_empty() {
+      return Ok("".to_owned());
+    }
+
+    let mut table = Table::new(self);
+    table.with(Style::sharp());
+
+    Ok(format!("{table}\n"))
+  }
+}
+
+/// CustomResourceDefinition that will cause upgrading it, or the operator that ships it, to fail
+#[derive(Debug, Serialize, Deserialize, Tabled)]
+#[tabled(rename_all = "UpperCase")]
+pub struct CustomResource {
+  #[tabled(inline)]
+  pub finding: finding::Finding,
+  pub name: String,
+  /// Well-known project that ships the CustomResourceDefinition, if it could be determined from its group
+  pub project: String,
+  #[tabled(rename = "STORED VERSIONS", display_with = "tabled_vec_to_string")]
+  pub stored_versions: Vec<String>,
+  #[tabled(display_with = "tabled_vec_to_string")]
+  pub issues: Vec<String>,
+}
+
+impl ToEntry for CustomResource {
+  fn to_entry(&self) -> finding::Entry {
+    finding::Entry {
+      api_version: Some("apiextensions.k8s.io/v1".to_string()),
+      ..finding::Entry::new(&self.finding, "CustomResourceDefinition", &self.name)
+    }
+  }
+}
+
+impl Findings for Vec<CustomResource> {
+  fn to_markdown_table(&self, leading_whitespace: &str) -> Result<String> {
+    if self.is_empty() {
+      return Ok(format!(
+        "{leading_whitespace}✅ - No issues were found with the CustomResourceDefinitions"
+      ));
+    }
+
+    let mut table = Table::new(self);
+    table
+      .with(Disable::column(ByColumnName::new("CHECK")))
+      .with(Margin::new(1, 0, 0, 0).fill('\t', 'x', 'x', 'x'))
+      .with(Style::markdown());
+
+    Ok(format!("{table}\n"))
+  }
+
+  fn to_stdout_table(&self) -> Result<String> {
+    if self.is_empty() {
+      return Ok("".to_owned());
+    }
+
+    let mut table = Table::new(self);
+    table.with(Style::sharp());
+
+    Ok(format!("{table}\n"))
+  }
+}
+
+/// Aggregated API that is not available, breaking API discovery for clients of the cluster
+#[derive(Debug, Serialize, Deserialize, Tabled)]
+#[tabled(rename_all = "UpperCase")]
+pub struct ApiService {
+  #[tabled(inline)]
+  pub finding: finding::Finding,
+  pub name: String,
+  /// Namespace of the Service backing the APIService
+  pub namespace: String,
+  pub service: String,
+  /// Reason and message of the `Available` condition
+  pub reason: String,
+}
+
+impl ToEntry for ApiService {
+  fn to_entry(&self) -> finding::Entry {
+    finding::Entry {
+      api_version: Some("apiregistration.k8s.io/v1".to_string()),
+      ..finding::Entry::new(&self.finding, "APIService", &self.name)
+    }
+  }
+}
+
+impl Findings for Vec<ApiService> {
+  fn to_markdown_table(&self, leading_whitespace: &str) -> Result<String> {
+    if self.is_empty() {
+      return Ok(format!("{leading_whitespace}✅ - All APIServices are available"));
+    }
+
+    let mut table = Table::new(self);
+    table
+      .with(Disable::column(ByColumnName::new("CHECK")))
+      .with(Margin::new(1, 0, 0, 0).fill('\t', 'x', 'x', 'x'))
+      .with(Style::markdown());
+
+    Ok(format!("{table}\n"))
+  }
+
+  fn to_stdout_table(&self) -> Result<String> {
+    if self.is_empty() {
+      return Ok("".to_owned());
+    }
+
+    let mut table = Table::new(self);
+    table.with(Style::sharp());
+
+    Ok(format!("{table}\n"))
+  }
+}
+
+/// K8S021 - check for APIServices that are not available
+///
+/// An unavailable aggregated API (i.e. - metrics-server) fails API discovery for clients such as `kubectl`,
+/// Helm, and controllers, and prevents namespaces from being deleted
+pub fn unavailable_api_services(api_services: &[APIService]) -> Vec<ApiService> {
+  api_services
+    .iter()
+    .filter_map(|api_service| {
+      let condition = api_service
+        .status
+        .as_ref()?
+        .conditions
+        .iter()
+        .flatten()
+        .find(|condition| condition.type_ == "Available")?;
+      if condition.status != "False" {
+        return None;
+      }
+
+      let service = api_service.spec.as_ref().and_then(|spec| spec.service.as_ref());
+      let reason = match (&condition.reason, &condition.message) {
+        (Some(reason), Some(message)) => format!("{reason}: {message}"),
+        (reason, message) => reason.as_ref().or(message.as_ref()).cloned().unwrap_or_default(),
+      };
+      let remediation = finding::Remediation::Required;
+
+      Some(ApiService {
+        finding: finding::Finding {
+          code: finding::Code::K8S021,
+          symbol: remediation.symbol(),
+          remediation,
+        },
+        name: api_service.metadata.name.to_owned().unwrap_or_default(),
+        namespace: service
+          .and_then(|service| service.namespace.to_owned())
+          .unwrap_or_else(|| "-".to_string()),
+        service: service
+          .and_then(|service| service.name.to_owned())
+          .unwrap_or_else(|| "-".to_string()),
+        reason,
+      })
+    })
+    .collect()
+}
+
+/// Node conditions that report an issue with the node when their status is `True`
+const PRESSURE_CONDITIONS: [&str; 4] = ["MemoryPressure", "DiskPressure", "PIDPressure", "NetworkUnavailable"];
+
+/// Node that is not ready, under pressure, or cordoned, as reported by Kubernetes
+#[derive(Debug, Serialize, Deserialize, Tabled)]
+#[tabled(rename_all = "UpperCase")]
+pub struct NodeHealth {
+  #[tabled(inline)]
+  pub finding: finding::Finding,
+  pub name: String,
+  /// The nodegroup, Fargate profile, or Karpenter node pool the node belongs to
+  pub nodegroup: String,
+  pub age: String,
+  #[tabled(display_with = "tabled_vec_to_string")]
+  pub taints: Vec<String>,
+  #[tabled(display_with = "tabled_vec_to_string")]
+  pub issues: Vec<String>,
+}
+
+impl ToEntry for NodeHealth {
+  fn to_entry(&self) -> finding::Entry {
+    finding::Entry {
+      api_version: Some("v1".to_string()),
+      ..finding::Entry::new(&self.finding, "Node", &self.name)
+    }
+  }
+}
+
+impl Findings for Vec<NodeHealth> {
+  fn to_markdown_table(&self, leading_whitespace: &str) -> Result<String> {
+    if self.is_empty() {
+      return Ok(format!(
+        "{leading_whitespace}✅ - All nodes are ready, schedulable, and free of pressure conditions"
+      ));
+    }
+
+    let mut table = Table::new(self);
+    table
+      .with(Disable::column(ByColumnName::new("CHECK")))
+      .with(Margin::new(1, 0, 0, 0).fill('\t', 'x', 'x', 'x'))
+      .with(Style::markdown());
+
+    Ok(format!("{table}\n"))
+  }
+
+  fn to_stdout_table(&self) -> Result<String> {
+    if self.is_empty() {
+      return Ok("".to_owned());
+    }
+
+    let mut table = Table::new(self);
+    table.with(Style::sharp());
+
+    Ok(format!("{table}\n"))
+  }
+}
+
+/// Returns the age of the object relative to now, in the largest whole unit (i.e. - `12d`, `5h`)
+fn display_age(created: Option<&Time>, now: DateTime<Utc>) -> String {
+  let created = match created {
+    Some(created) => created.0,
+    None => return "-".to_string(),
+  };
+
+  let age = now.signed_duration_since(created);
+  if age.num_days() > 0 {
+    format!("{}d", age.num_days())
+  } else if age.num_hours() > 0 {
+    format!("{}h", age.num_hours())
+  } else {
+    format!("{}m", age.num_minutes().max(0))
+  }
+}
+
+/// K8S022 - check the health of the nodes as reported by Kubernetes
+///
+/// Complements the nodegroup health reported by Amazon EKS (EKS003), which does not cover nodes that
+/// Amazon EKS considers healthy but Kubernetes does not
+///
+/// - Nodes that are not ready, or report a pressure condition, are required to be remediated
+/// - Nodes that are already cordoned are recommended to be remediated, since they are either being
+///   drained or were left unschedulable, and reduce the capacity available to the pods evicted during the upgrade
+pub fn node_health(nodes: &[resources::Node], now: DateTime<Utc>) -> Vec<NodeHealth> {
+  nodes
+    .iter()
+    .filter_map(|node| {
+      let mut required = Vec::new();
+      match node.conditions.get("Ready").map(String::as_str) {
+        Some("True") => {}
+        Some("False") => required.push("NotReady".to_string()),
+        status => required.push(format!("Ready is {}", status.unwrap_or("Unknown"))),
+      }
+      for condition in PRESSURE_CONDITIONS {
+        if node.conditions.get(condition).map(String::as_str) == Some("True") {
+          required.push(condition.to_string());
+        }
+      }
+
+      let recommended = if node.unschedulable {
+        vec!["Cordoned".to_string()]
+      } else {
+        vec![]
+      };
+
+      let remediation = match (required.is_empty(), recommended.is_empty()) {
+        (false, _) => finding::Remediation::Required,
+        (true, false) => finding::Remediation::Recommended,
+        (true, true) => return None,
+      };
+
+      Some(NodeHealth {
+        finding: finding::Finding {
+          code: finding::Code::K8S022,
+          symbol: remediation.symbol(),
+          remediation,
+        },
+        name: node.name.to_owned(),
+        nodegroup: get_nodegroup(node),
+        age: display_age(node.created.as_ref(), now),
+        taints: node.taints.to_owned(),
+        issues: required.into_iter().chain(recommended).collect(),
+      })
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::BTreeMap;
+
+  use super::*;
+
+  fn node(name: &str, version: &str, nodegroup: Option<&str>) -> resources::Node {
+    resources::Node {
+      name: name.to_string(),
+      labels: nodegroup.map(|ng| BTreeMap::from([("alpha.eksctl.io/nodegroup-name".to_string(), ng.to_string())])),
+      kubelet_version: version.to_string(),
+      version: version.parse().unwrap(),
+      conditions: BTreeMap::from([("Ready".to_string(), "True".to_string())]),
+      unschedulable: false,
+      taints: vec![],
+      created: None,
+    }
+  }
+
+  #[test]
+  fn can_check_node_health() {
+    let now = Utc::now();
+    let mut nodes = vec![
+      node("healthy", "v1.28.1", Some("a")),
+      node("not-ready", "v1.28.1", Some("a")),
+      node("pressure", "v1.28.1", Some("a")),
+      node("cordoned", "v1.28.1", Some("b")),
+    ];
+    nodes[1].conditions.insert("Ready".to_string(), "False".to_string());
+    nodes[2]
+      .conditions
+      .insert("DiskPressure".to_string(), "True".to_string());
+    nodes[2]
+      .conditions
+      .insert("MemoryPressure".to_string(), "False".to_string());
+    nodes[3].unschedulable = true;
+    nodes[3].taints = vec!["node.kubernetes.io/unschedulable:NoSchedule".to_string()];
+    nodes[3].created = Some(Time(now - k8s_openapi::chrono::Duration::hours(50)));
+
+    let findings = node_health(&nodes, now);
+    let issues: Vec<(&str, String, Vec<&str>)> = findings
+      .iter()
+      .map(|f| {
+        (
+          f.name.as_str(),
+          f.finding.remediation.to_string(),
+          f.issues.iter().map(String::as_str).collect(),
+        )
+      })
+      .collect();
+    assert_eq!(
+      issues,
+      vec![
+        ("not-ready", "Required".to_string(), vec!["NotReady"]),
+        ("pressure", "Required".to_string(), vec!["DiskPressure"]),
+        ("cordoned", "Recommended".to_string(), vec!["Cordoned"]),
+      ]
+    );
+    assert_eq!(findings[2].age, "2d");
+    assert_eq!(findings[0].age, "-");
+  }
+
+  #[tokio::test]
+  async fn can_check_version_skew() {
+    let nodes = vec![
+      node("current", "v1.28.1", Some("a")),
+      node("one", "v1.27.1", Some("a")),
+      node("two", "v1.26.1", Some("b")),
+      node("three", "v1.25.1", None),
+    ];
+
+    // Upgrading to 1.29 permits n-3 skew, nodes within 3 minor versions of 1.29 remain within policy
+    let findings = version_skew(&nodes, &version::Version::new(1, 28), &version::Version::new(1, 29))
+      .await
+      .unwrap();
+    let remediations: Vec<(String, String, String)> = findings
+      .iter()
+      .map(|f| {
+        (
+          f.name.to_owned(),
+          f.finding.remediation.to_string(),
+          f.target_version_skew.to_owned(),
+        )
+      })
+      .collect();
+
+    assert_eq!(
+      remediations,
+      vec![
+        ("one".to_string(), "Recommended".to_string(), "+2".to_string()),
+        ("two".to_string(), "Recommended".to_string(), "+3".to_string()),
+        ("three".to_string(), "Required".to_string(), "+4".to_string()),
+      ]
+    );
+    assert_eq!(findings[2].nodegroup, "-");
+
+    // Upgrading to 1.27 permits n-2 skew
+    let findings = version_skew(
+      &nodes[2..],
+      &version::Version::new(1, 26),
+      &version::Version::new(1, 27),
+    )
+    .await
+    .unwrap();
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].finding.remediation.to_string(), "Recommended");
+  }
+
+  #[test]
+  fn can_check_pod_placement() {
+    let zone_node = |name: &str, nodegroup: &str, zone_label: &str, zone: &str| {
+      let mut node = node(name, "v1.28.1", Some(nodegroup));
+      if let Some(labels) = node.labels.as_mut() {
+        labels.insert(zone_label.to_string(), zone.to_string());
+      }
+      node
+    };
+    let [zone_label, beta_zone_label] = resources::ZONE_TOPOLOGY_KEYS;
+    let nodes = vec![
+      zone_node("a1", "a", zone_label, "us-east-1a"),
+      zone_node("a2", "a", zone_label, "us-east-1a"),
+      zone_node("b1", "b", zone_label, "us-east-1a"),
+      zone_node("c1", "c", zone_label, "us-east-1b"),
+      // Only labeled with the deprecated beta label
+      zone_node("d1", "d", beta_zone_label, "us-east-1b"),
+    ];
+    let pod = |owner_kind: &str, owner: &str, node: &str, phase: &str| -> core::v1::Pod {
+      serde_yaml::from_str(&format!(
+        r#"
+metadata:
+  namespace: default
+  ownerReferences: [{{apiVersion: apps/v1, kind: {owner_kind}, name: {owner}, uid: "1", controller: true}}]
+spec: {{nodeName: {node}, containers: []}}
+status: {{phase: {phase}}}
+"#
+      ))
+      .unwrap()
+    };
+    let owners = resources::Owners::from([(
+      ("default".to_string(), "ReplicaSet".to_string(), "web-5d4f8".to_string()),
+      serde_yaml::from_str("{apiVersion: apps/v1, kind: Deployment, name: web, uid: '2', controller: true}").unwrap(),
+    )]);
+
+    let pods = vec![
+      // Resolved through the ReplicaSet to the Deployment
+      pod("ReplicaSet", "web-5d4f8", "a1", "Running"),
+      pod("ReplicaSet", "web-5d4f8", "a1", "Running"),
+      pod("ReplicaSet", "web-5d4f8", "c1", "Pending"),
+      pod("StatefulSet", "db", "a1", "Running"),
+      pod("StatefulSet", "db", "a2", "Running"),
+      pod("StatefulSet", "cache", "a1", "Running"),
+      pod("StatefulSet", "cache", "b1", "Running"),
+      pod("StatefulSet", "spread", "a1", "Running"),
+      pod("StatefulSet", "spread", "c1", "Running"),
+      pod("StatefulSet", "single", "a1", "Running"),
+      pod("StatefulSet", "legacy", "c1", "Running"),
+      pod("StatefulSet", "legacy", "d1", "Running"),
+      pod("DaemonSet", "agent", "a1", "Running"),
+      pod("DaemonSet", "agent", "a1", "Running"),
+    ];
+
+    let findings = pod_placement(&pods, &nodes, &owners);
+    let issues: Vec<(&str, &str, String)> = findings
+      .iter()
+      .map(|f| (f.name.as_str(), f.issue.as_str(), f.finding.remediation.to_string()))
+      .collect();
+    assert_eq!(
+      issues,
+      vec![
+        ("web", "All replicas on node a1", "Required".to_string()),
+        ("cache", "All replicas in zone us-east-1a", "Recommended".to_string()),
+        ("db", "All replicas in nodegroup a", "Recommended".to_string()),
+        ("legacy", "All replicas in zone us-east-1b", "Recommended".to_string()),
+      ]
+    );
+    assert_eq!(findings[0].kind, "Deployment");
+    assert_eq!(findings[0].replicas, 2);
+  }
+
+  #[test]
+  fn can_check_eviction_blockers() {
+    let pods: Vec<core::v1::Pod> = serde_yaml::from_str(
+      r#"
+- metadata: {name: ok, namespace: default}
+  spec: {nodeName: a1, containers: []}
+- metadata:
+    name: annotated
+    namespace: default
+    annotations: {karpenter.sh/do-not-disrupt: "true", cluster-autoscaler.kubernetes.io/safe-to-evict: "true"}
+  spec: {nodeName: a1, containers: []}
+- metadata: {name: scratch, namespace: default}
+  spec:
+    nodeName: a1
+    containers: []
+    volumes: [{name: tmp, emptyDir: {}}, {name: logs, hostPath: {path: /var/log}}]
+- metadata: {name: affinity, namespace: default}
+  spec:
+    nodeName: a1
+    containers: []
+    affinity:
+      nodeAffinity:
+        requiredDuringSchedulingIgnoredDuringExecution:
+          nodeSelectorTerms: [{matchExpressions: [{key: kubernetes.io/hostname, operator: In, values: [a1]}]}]
+- metadata:
+    name: pinned-x2k9p
+    namespace: default
+    ownerReferences: [{apiVersion: apps/v1, kind: StatefulSet, name: pinned, uid: "1", controller: true}]
+  spec: {nodeName: a1, containers: []}
+- metadata:
+    name: agent-x2k9p
+    namespace: default
+    ownerReferences: [{apiVersion: apps/v1, kind: DaemonSet, name: agent, uid: "2", controller: true}]
+  spec: {nodeName: a1, containers: [], volumes: [{name: logs, hostPath: {path: /var/log}}]}
+"#,
+    )
+    .unwrap();
+    let statefulset: k8s_openapi::api::apps::v1::StatefulSet = serde_yaml::from_str(
+      "metadata: {name: pinned, namespace: default}\nspec: {selector: {}, serviceName: pinned, template: {spec: {nodeName: a1, containers: []}}}",
+    )
+    .unwrap();
+    let resources = vec![resources::StdResource::from(&statefulset)];
+
+    let findings = eviction_blockers(&pods, &resources, Some(&resources::Owners::new()));
+    let blockers: Vec<(&str, String, Vec<&str>)> = findings
+      .iter()
+      .map(|f| {
+        (
+          f.name.as_str(),
+          f.finding.remediation.to_string(),
+          f.reasons.iter().map(String::as_str).collect(),
+        )
+      })
+      .collect();
+    assert_eq!(
+      blockers,
+      vec![
+        (
+          "annotated",
+          "Required".to_string(),
+          vec!["karpenter.sh/do-not-disrupt: \"true\""]
+        ),
+        (
+          "scratch",
+          "Recommended".to_string(),
+          vec!["emptyDir volume tmp", "hostPath volume logs"]
+        ),
+        ("affinity", "Required".to_string(), vec!["Pinned to node a1"]),
+        (
+          "pinned-x2k9p",
+          "Required".to_string(),
+          vec!["Pinned to node a1 (nodeName)"]
+        ),
+      ]
+    );
+
+    // Pods are still checked when their owners could not be collected
+    assert_eq!(eviction_blockers(&pods, &resources, None).len(), findings.len());
+  }
+
+  #[test]
+  fn can_check_unavailable_api_services() {
+    let api_services: Vec<APIService> = serde_yaml::from_str(
+      r#"
+- metadata: {name: v1.apps}
+  spec: {group: apps, version: v1, groupPriorityMinimum: 17800, versionPriority: 15}
+  status: {conditions: [{type: Available, status: "True", reason: Local}]}
+- metadata: {name: v1beta1.metrics.k8s.io}
+  spec:
+    group: metrics.k8s.io
+    version: v1beta1
+    groupPriorityMinimum: 100
+    versionPriority: 100
+    service: {namespace: kube-system, name: metrics-server}
+  status:
+    conditions:
+      - type: Available
+        status: "False"
+        reason: MissingEndpoints
+        message: endpoints for service/metrics-server in "kube-system" have no addresses
+"#,
+    )
+    .unwrap();
+
+    let findings = unavailable_api_services(&api_services);
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].name, "v1beta1.metrics.k8s.io");
+    assert_eq!(findings[0].namespace, "kube-system");
+    assert_eq!(findings[0].service, "metrics-server");
+    assert!(findings[0].reason.starts_with("MissingEndpoints: "));
+  }
+
+  #[test]
+  fn can_check_bare_pods() {
+    let pods: Vec<core::v1::Pod> = serde_yaml::from_str(
+      r#"
+- metadata: {name: bare, namespace: default}
+  spec: {nodeName: a1, containers: []}
+  status: {phase: Running}
+- metadata: {name: completed, namespace: default}
+  status: {phase: Succeeded}
+- metadata:
+    name: kube-proxy-a1
+    namespace: kube-system
+    ownerReferences: [{apiVersion: v1, kind: Node, name: a1, uid: "1", controller: true}]
+- metadata:
+    name: web-5d4f8-x2k9p
+    namespace: default
+    ownerReferences: [{apiVersion: apps/v1, kind: ReplicaSet, name: web-5d4f8, uid: "2", controller: true}]
+"#,
+    )
+    .unwrap();
+
+    let findings = bare_pods(&pods);
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].name, "bare");
+    assert_eq!(findings[0].node, "a1");
+  }
+
+  #[test]
+  fn can_check_blocking_pod_disruption_budgets() {
+    let pdb = |name: &str, spec: &str, status: &str| -> policy::v1::PodDisruptionBudget {
+      serde_yaml::from_str(&format!(
+        "metadata: {{name: {name}, namespace: default}}\nspec: {spec}\nstatus: {status}"
+      ))
+      .unwrap()
+    };
+    let pod = |app: &str, phase: &str| -> core::v1::Pod {
+      serde_yaml::from_str(&format!(
+        "metadata: {{name: {app}, namespace: default, labels: {{app: {app}}}}}\nstatus: {{phase: {phase}}}"
+      ))
+      .unwrap()
+    };
+    let status = |expected: i32, healthy: i32, allowed: i32| {
+      format!(
+        "{{expectedPods: {expected}, currentHealthy: {healthy}, desiredHealthy: 0, disruptionsAllowed: {allowed}}}"
+      )
+    };
+
+    let pdbs = vec![
+      pdb(
+        "ok",
+        "{selector: {matchLabels: {app: ok}}, maxUnavailable: 1}",
+        &status(3, 3, 1),
+      ),
+      pdb(
+        "zero",
+        "{selector: {matchLabels: {app: zero}}, maxUnavailable: 0%}",
+        &status(3, 3, 0),
+      ),
+      pdb(
+        "min",
+        "{selector: {matchLabels: {app: min}}, minAvailable: 2}",
+        &status(2, 2, 0),
+      ),
+      pdb(
+        "unhealthy",
+        "{selector: {matchLabels: {app: unhealthy}}, minAvailable: 2}",
+        &status(3, 2, 0),
+      ),
+      pdb(
+        "rollout",
+        "{selector: {matchLabels: {app: rollout}}, maxUnavailable: 1}",
+        &status(3, 2, 0),
+      ),
+      pdb(
+        "completed",
+        "{selector: {matchLabels: {app: completed}}, maxUnavailable: 1}",
+        &status(0, 0, 0),
+      ),
+      pdb(
+        "none",
+        "{selector: {matchLabels: {app: none}}, minAvailable: 1}",
+        &status(0, 0, 0),
+      ),
+      pdb(
+        "overlap-a",
+        "{selector: {matchLabels: {app: overlap}}, maxUnavailable: 1}",
+        &status(2, 2, 1),
+      ),
+      pdb("overlap-b", "{selector: {}, maxUnavailable: 1}", &status(9, 9, 1)),
+    ];
+    let mut pods: Vec<core::v1::Pod> = ["ok", "zero", "min", "unhealthy", "rollout", "overlap"]
+      .into_iter()
+      .map(|app| pod(app, "Running"))
+      .collect();
+    pods.push(pod("completed", "Succeeded"));
+
+    // An empty selector selects all pods in the namespace, overlapping with every other PDB
+    let findings = blocking_pod_disruption_budgets(&pdbs[..8], &pods);
+    let reasons: Vec<(&str, &str)> = findings.iter().map(|f| (f.name.as_str(), f.reason.as_str())).collect();
+    assert_eq!(
+      reasons,
+      vec![
+        ("zero", "maxUnavailable is 0"),
+        ("min", "minAvailable >= healthy pods"),
+        ("unhealthy", "minAvailable >= healthy pods"),
+        ("rollout", "No disruptions currently allowed"),
+        ("completed", "Selects no pods"),
+        ("none", "Selects no pods"),
+      ]
+    );
+    let remediations: Vec<String> = findings.iter().map(|f| f.finding.remediation.to_string()).collect();
+    assert_eq!(
+      remediations,
+      vec![
+        "Required",
+        "Required",
+        "Required",
+        "Recommended",
+        "Recommended",
+        "Recommended"
+      ]
+    );
+
+    let findings = blocking_pod_disruption_budgets(&pdbs, &pods);
+    assert_eq!(findings.len(), 9);
+    assert!(findings
+      .iter()
+      .filter(|f| f.name.starts_with("overlap"))
+      .all(|f| f.reason == "Pods are selected by multiple PDBs"));
+  }
 }
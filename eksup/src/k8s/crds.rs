@@ -0,0 +1,286 @@
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
+use kube::{api::Api, Client};
+use tracing::warn;
+
+use crate::{
+  finding,
+  k8s::{checks, webhooks},
+};
+
+/// API version of CustomResourceDefinitions removed in Kubernetes v1.22
+const V1BETA1_API_VERSION: &str = "apiextensions.k8s.io/v1beta1";
+
+/// API groups of the custom resources shipped by well-known projects, and the name of the project
+///
+/// A group matches either exactly or as a suffix (i.e. - `acme.cert-manager.io` matches `cert-manager.io`)
+const WELL_KNOWN_PROJECTS: [(&str, &str); 17] = [
+  ("argoproj.io", "Argo"),
+  ("cert-manager.io", "cert-manager"),
+  ("crd.k8s.amazonaws.com", "Amazon VPC CNI"),
+  ("elbv2.k8s.aws", "AWS Load Balancer Controller"),
+  ("external-secrets.io", "External Secrets Operator"),
+  ("fluxcd.io", "Flux"),
+  ("gatekeeper.sh", "Gatekeeper"),
+  ("istio.io", "Istio"),
+  ("karpenter.k8s.aws", "Karpenter"),
+  ("karpenter.sh", "Karpenter"),
+  ("keda.sh", "KEDA"),
+  ("kyverno.io", "Kyverno"),
+  ("monitoring.coreos.com", "Prometheus Operator"),
+  ("projectcalico.org", "Calico"),
+  ("traefik.containo.us", "Traefik"),
+  ("velero.io", "Velero"),
+  ("vpcresources.k8s.aws", "Amazon VPC resource controller"),
+];
+
+/// Returns the well-known project that ships the custom resources of the API group, if any
+fn get_project(group: &str) -> Option<&'static str> {
+  WELL_KNOWN_PROJECTS
+    .iter()
+    .find(|(suffix, _)| group == *suffix || group.ends_with(&format!(".{suffix}")))
+    .map(|(_, project)| *project)
+}
+
+/// Returns the reasons the CustomResourceDefinition appears to still be defined through the
+/// `apiextensions.k8s.io/v1beta1` API, which only remain in effect until the CRD is next updated
+fn legacy_schema(crd: &CustomResourceDefinition) -> Vec<String> {
+  let mut reasons = Vec::new();
+
+  let written = crd
+    .metadata
+    .managed_fields
+    .iter()
+    .flatten()
+    .any(|field| field.api_version.as_deref() == Some(V1BETA1_API_VERSION));
+  if written {
+    reasons.push(format!("Last written through {V1BETA1_API_VERSION}"));
+  }
+
+  // Only permitted for CRDs created through v1beta1; v1 requires a structural schema that prunes unknown fields
+  if crd.spec.preserve_unknown_fields == Some(true) {
+    reasons.push("preserveUnknownFields is enabled".to_string());
+  }
+
+  for version in &crd.spec.versions {
+    let schema = version
+      .schema
+      .as_ref()
+      .and_then(|schema| schema.open_api_v3_schema.as_ref());
+    if schema.is_none() {
+      reasons.push(format!("Version {} has no schema", version.name));
+    }
+  }
+
+  reasons
+}
+
+/// K8S020 - check the CustomResourceDefinitions for issues that cause upgrading them, or their objects, to fail
+///
+/// - Remediation is required when a version in `status.storedVersions` is no longer served, or when the
+///   conversion webhook of the CRD has no ready endpoints
+/// - Remediation is recommended when the CRD of a well-known project appears to still be defined through
+///   `apiextensions.k8s.io/v1beta1`, since the CRDs are updated along with the project
+fn check_crds(
+  crds: &[CustomResourceDefinition],
+  endpoints: &webhooks::ServiceEndpoints,
+) -> Vec<checks::CustomResource> {
+  crds
+    .iter()
+    .filter_map(|crd| {
+      let mut required = Vec::new();
+
+      let stored_versions = crd
+        .status
+        .as_ref()
+        .and_then(|status| status.stored_versions.to_owned())
+        .unwrap_or_default();
+      for stored in &stored_versions {
+        let served = crd
+          .spec
+          .versions
+          .iter()
+          .any(|version| &version.name == stored && version.served);
+        if !served {
+          required.push(format!("Stored version {stored} is not served"));
+        }
+      }
+
+      let service = crd
+        .spec
+        .conversion
+        .as_ref()
+        .filter(|conversion| conversion.strategy == "Webhook")
+        .and_then(|conversion| conversion.webhook.as_ref())
+        .and_then(|webhook| webhook.client_config.as_ref())
+        .and_then(|config| config.service.as_ref());
+      if let Some(service) = service {
+        if webhooks::ready_addresses(endpoints, &service.namespace, &service.name).is_empty() {
+          required.push(format!(
+            "Conversion webhook service {}/{} has no ready endpoints",
+            service.namespace, service.name
+          ));
+        }
+      }
+
+      let project = get_project(&crd.spec.group);
+      let recommended = match project {
+        Some(_) => legacy_schema(crd),
+        None => Vec::new(),
+      };
+
+      let remediation = match (required.is_empty(), recommended.is_empty()) {
+        (false, _) => finding::Remediation::Required,
+        (true, false) => finding::Remediation::Recommended,
+        (true, true) => return None,
+      };
+
+      Some(checks::CustomResource {
+        finding: finding::Finding {
+          code: finding::Code::K8S020,
+          symbol: remediation.symbol(),
+          remediation,
+        },
+        name: crd.metadata.name.to_owned().unwrap_or_default(),
+        project: project.unwrap_or("-").to_string(),
+        stored_versions,
+        issues: required.into_iter().chain(recommended).collect(),
+      })
+    })
+    .collect()
+}
+
+/// K8S020 - check the CustomResourceDefinitions of the cluster for stored versions, conversion webhooks,
+/// and schemas that will cause upgrades of the CRDs, or of the operators that ship them, to fail
+pub(crate) async fn custom_resources(client: &Client) -> Result<Vec<checks::CustomResource>> {
+  let api: Api<CustomResourceDefinition> = Api::all(client.to_owned());
+  let crds = match api.list(&Default::default()).await {
+    Ok(list) => list.items,
+    Err(err) => {
+      warn!("Unable to list CustomResourceDefinitions: {err}");
+      return Ok(vec![]);
+    }
+  };
+
+  let services: BTreeSet<(String, String)> = crds
+    .iter()
+    .filter_map(|crd| {
+      crd
+        .spec
+        .conversion
+        .as_ref()?
+        .webhook
+        .as_ref()?
+        .client_config
+        .as_ref()?
+        .service
+        .as_ref()
+    })
+    .map(|service| (service.namespace.to_owned(), service.name.to_owned()))
+    .collect();
+  let endpoints = match webhooks::get_endpoints(client, services).await {
+    Ok(endpoints) => endpoints,
+    Err(err) => {
+      warn!("Unable to get the endpoints of the CustomResourceDefinition conversion webhook services: {err}");
+      return Ok(vec![]);
+    }
+  };
+
+  Ok(check_crds(&crds, &endpoints))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn can_check_crds() {
+    let crds: Vec<CustomResourceDefinition> = serde_yaml::from_str(
+      r#"
+- metadata: {name: widgets.example.com}
+  spec:
+    group: example.com
+    names: {kind: Widget, plural: widgets}
+    scope: Namespaced
+    versions: [{name: v1, served: true, storage: true, schema: {openAPIV3Schema: {type: object}}}]
+  status: {storedVersions: [v1]}
+- metadata: {name: certificates.cert-manager.io}
+  spec:
+    group: cert-manager.io
+    names: {kind: Certificate, plural: certificates}
+    scope: Namespaced
+    versions:
+      - {name: v1alpha2, served: false, storage: false, schema: {openAPIV3Schema: {type: object}}}
+      - {name: v1, served: true, storage: true, schema: {openAPIV3Schema: {type: object}}}
+    conversion:
+      strategy: Webhook
+      webhook:
+        conversionReviewVersions: [v1]
+        clientConfig: {service: {namespace: cert-manager, name: cert-manager-webhook}}
+  status: {storedVersions: [v1alpha2, v1]}
+- metadata:
+    name: servicemonitors.monitoring.coreos.com
+    managedFields: [{apiVersion: apiextensions.k8s.io/v1beta1, manager: kubectl}]
+  spec:
+    group: monitoring.coreos.com
+    names: {kind: ServiceMonitor, plural: servicemonitors}
+    scope: Namespaced
+    preserveUnknownFields: true
+    versions: [{name: v1, served: true, storage: true}]
+  status: {storedVersions: [v1]}
+- metadata:
+    name: gadgets.example.com
+    managedFields: [{apiVersion: apiextensions.k8s.io/v1beta1, manager: kubectl}]
+  spec:
+    group: example.com
+    names: {kind: Gadget, plural: gadgets}
+    scope: Namespaced
+    preserveUnknownFields: true
+    versions: [{name: v1, served: true, storage: true}]
+  status: {storedVersions: [v1]}
+"#,
+    )
+    .unwrap();
+
+    let findings = check_crds(&crds, &webhooks::ServiceEndpoints::new());
+    let issues: Vec<(&str, &str, String, Vec<&str>)> = findings
+      .iter()
+      .map(|f| {
+        (
+          f.name.as_str(),
+          f.project.as_str(),
+          f.finding.remediation.to_string(),
+          f.issues.iter().map(String::as_str).collect(),
+        )
+      })
+      .collect();
+    assert_eq!(
+      issues,
+      vec![
+        (
+          "certificates.cert-manager.io",
+          "cert-manager",
+          "Required".to_string(),
+          vec![
+            "Stored version v1alpha2 is not served",
+            "Conversion webhook service cert-manager/cert-manager-webhook has no ready endpoints"
+          ]
+        ),
+        (
+          "servicemonitors.monitoring.coreos.com",
+          "Prometheus Operator",
+          "Recommended".to_string(),
+          vec![
+            "Last written through apiextensions.k8s.io/v1beta1",
+            "preserveUnknownFields is enabled",
+            "Version v1 has no schema"
+          ]
+        ),
+      ]
+    );
+    assert_eq!(get_project("acme.cert-manager.io"), Some("cert-manager"));
+    assert_eq!(get_project("notcert-manager.io"), None);
+  }
+}
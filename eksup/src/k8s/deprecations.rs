@@ -0,0 +1,340 @@
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::{Context, Result};
+use kube::{
+  api::{Api, ApiResource, DynamicObject, GroupVersionKind},
+  Client,
+};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::{
+  finding,
+  k8s::{checks, resources},
+  playbook::Templates,
+  version,
+};
+
+/// Annotation set by `kubectl apply` containing the last applied manifest
+const LAST_APPLIED_ANNOTATION: &str = "kubectl.kubernetes.io/last-applied-configuration";
+
+/// Metric reported by the API server for requests made to deprecated APIs
+/// <https://kubernetes.io/blog/2020/09/03/warnings/#metrics>
+const REQUESTED_DEPRECATED_APIS_METRIC: &str = "apiserver_requested_deprecated_apis";
+
+/// A Kubernetes API (group, version, and kind) that is removed from the API server
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct DeprecatedApi {
+  /// Group and version of the API (i.e. - `extensions/v1beta1`)
+  pub api_version: String,
+  pub kind: String,
+  /// Plural name of the resource served by the API (i.e. - `ingresses`)
+  pub resource: String,
+  /// API version that replaces the removed API, if there is one
+  pub replacement: Option<String>,
+}
+
+impl DeprecatedApi {
+  fn gvk(&self) -> GroupVersionKind {
+    let (group, version) = split_api_version(&self.api_version);
+    GroupVersionKind::gvk(group, version, &self.kind)
+  }
+}
+
+/// Splits an API version into its group and version, where the core group is represented by an empty string
+pub(crate) fn split_api_version(api_version: &str) -> (&str, &str) {
+  api_version.split_once('/').unwrap_or(("", api_version))
+}
+
+/// Returns the removed APIs, keyed by the version they are removed in
+///
+/// The table of removed APIs is embedded into the binary from `deprecations.yaml`
+/// <https://kubernetes.io/docs/reference/using-api/deprecation-guide/>
+fn load_deprecated_apis() -> Result<BTreeMap<version::Version, Vec<DeprecatedApi>>> {
+  let embedded = Templates::get("deprecations.yaml").context("Deprecated API data not found")?;
+
+  Ok(serde_yaml::from_str(std::str::from_utf8(embedded.data.as_ref())?)?)
+}
+
+/// Returns the APIs removed at or before the target version, keyed by the version they are removed in
+pub(crate) fn get_deprecated_apis(
+  target_version: &version::Version,
+) -> Result<BTreeMap<version::Version, Vec<DeprecatedApi>>> {
+  let mut deprecations = load_deprecated_apis()?;
+  deprecations.retain(|removed_in, _| removed_in <= target_version);
+
+  Ok(deprecations)
+}
+
+/// Returns the APIs removed after the version provided, which a cluster of that version may still serve
+///
+/// APIs removed at or before the version of the cluster are no longer served, and are therefore never queried
+pub(crate) fn get_apis_removed_after(version: &version::Version) -> Result<Vec<DeprecatedApi>> {
+  let mut deprecations = load_deprecated_apis()?;
+  deprecations.retain(|removed_in, _| removed_in > version);
+
+  Ok(deprecations.into_values().flatten().collect())
+}
+
+/// Returns the group versions (i.e. - `extensions/v1beta1`) currently served by the API server
+async fn get_served_api_versions(client: &Client) -> Result<HashSet<String>> {
+  let groups = client.list_api_groups().await?;
+
+  Ok(
+    groups
+      .groups
+      .into_iter()
+      .flat_map(|group| group.versions)
+      .map(|version| version.group_version)
+      .collect(),
+  )
+}
+
+/// Returns true if the object was last written through the API version provided
+///
+/// This is determined from the API versions recorded by each field manager, or
+/// from the manifest last applied with `kubectl apply`
+fn written_with(object: &DynamicObject, api_version: &str) -> bool {
+  let managed = object
+    .metadata
+    .managed_fields
+    .as_ref()
+    .map(|fields| fields.iter().any(|f| f.api_version.as_deref() == Some(api_version)))
+    .unwrap_or(false);
+
+  let applied = object
+    .metadata
+    .annotations
+    .as_ref()
+    .and_then(|annotations| annotations.get(LAST_APPLIED_ANNOTATION))
+    .and_then(|applied| serde_json::from_str::<serde_json::Value>(applied).ok())
+    .map(|applied| applied["apiVersion"] == api_version)
+    .unwrap_or(false);
+
+  managed || applied
+}
+
+/// Returns the objects of the removed API that were last written through the removed API version
+async fn get_objects(client: &Client, api: &DeprecatedApi) -> Result<Vec<DynamicObject>> {
+  let resource = ApiResource::from_gvk_with_plural(&api.gvk(), &api.resource);
+  let dynamic: Api<DynamicObject> = Api::all_with(client.to_owned(), &resource);
+  let list = dynamic.list(&Default::default()).await?;
+
+  Ok(
+    list
+      .items
+      .into_iter()
+      .filter(|object| written_with(object, &api.api_version))
+      .collect(),
+  )
+}
+
+/// Request to a deprecated API as recorded by the API server metrics
+#[derive(Debug, PartialEq)]
+struct RequestedApi {
+  api_version: String,
+  resource: String,
+  removed_in: version::Version,
+}
+
+/// Parses the `apiserver_requested_deprecated_apis` series from the API server metrics
+///
+/// Series for APIs that are deprecated without a planned removal are omitted
+fn parse_requested_apis(metrics: &str) -> Vec<RequestedApi> {
+  metrics
+    .lines()
+    .filter_map(|line| line.strip_prefix(REQUESTED_DEPRECATED_APIS_METRIC)?.strip_prefix('{'))
+    .filter_map(|line| {
+      let (labels, value) = line.rsplit_once('}')?;
+      if value.trim().parse::<f64>().ok()? <= 0.0 {
+        return None;
+      }
+
+      let labels: BTreeMap<&str, &str> = labels
+        .split(',')
+        .filter_map(|label| label.split_once('='))
+        .map(|(key, value)| (key.trim(), value.trim().trim_matches('"')))
+        .collect();
+
+      let group = labels.get("group").copied().unwrap_or_default();
+      let version = labels.get("version")?;
+      let api_version = match group {
+        "" => version.to_string(),
+        group => format!("{group}/{version}"),
+      };
+
+      Some(RequestedApi {
+        api_version,
+        resource: labels.get("resource")?.to_string(),
+        removed_in: labels.get("removed_release")?.parse().ok()?,
+      })
+    })
+    .collect()
+}
+
+/// Returns the deprecated API requests recorded by the API server since it was last restarted
+async fn get_requested_apis(client: &Client) -> Result<Vec<RequestedApi>> {
+  let request = http::Request::get("/metrics").body(vec![])?;
+  let metrics = client.request_text(request).await?;
+
+  Ok(parse_requested_apis(&metrics))
+}
+
+fn new_finding() -> finding::Finding {
+  let remediation = finding::Remediation::Required;
+
+  finding::Finding {
+    code: finding::Code::K8S012,
+    symbol: remediation.symbol(),
+    remediation,
+  }
+}
+
+/// K8S012 - check for APIs removed at or before the target version that are still in use
+///
+/// Usage is detected from the objects last written through a removed API that is still served
+/// by the cluster, as well as from the requests to removed APIs recorded by the API server
+pub(crate) async fn removed_apis(
+  client: &Client,
+  target_version: &version::Version,
+) -> Result<Vec<checks::RemovedApi>> {
+  let deprecations = get_deprecated_apis(target_version)?;
+  let served = get_served_api_versions(client).await?;
+
+  let mut findings = Vec::new();
+  for (removed_in, apis) in &deprecations {
+    for api in apis.iter().filter(|api| served.contains(&api.api_version)) {
+      let objects = match get_objects(client, api).await {
+        Ok(objects) => objects,
+        Err(err) => {
+          warn!("Unable to list {} {}: {err}", api.api_version, api.resource);
+          continue;
+        }
+      };
+
+      findings.extend(objects.into_iter().map(|object| checks::RemovedApi {
+        finding: new_finding(),
+        api_version: api.api_version.to_owned(),
+        kind: api.kind.to_owned(),
+        namespace: object.metadata.namespace.unwrap_or_default(),
+        name: object.metadata.name.unwrap_or_default(),
+        removed_in: *removed_in,
+        replacement: api.replacement.to_owned(),
+        source: "object".to_string(),
+      }));
+    }
+  }
+
+  let requested = match get_requested_apis(client).await {
+    Ok(requested) => requested,
+    Err(err) => {
+      warn!("Unable to retrieve API server metrics for deprecated API requests: {err}");
+      vec![]
+    }
+  };
+
+  findings.extend(
+    requested
+      .into_iter()
+      .filter(|requested| requested.removed_in <= *target_version)
+      .map(|requested| {
+        let api = deprecations.get(&requested.removed_in).and_then(|apis| {
+          apis
+            .iter()
+            .find(|api| api.api_version == requested.api_version && api.resource == requested.resource)
+        });
+
+        checks::RemovedApi {
+          finding: new_finding(),
+          api_version: requested.api_version.to_owned(),
+          kind: api.map(|api| api.kind.to_owned()).unwrap_or(requested.resource),
+          namespace: String::new(),
+          name: "*".to_string(),
+          removed_in: requested.removed_in,
+          replacement: api.and_then(|api| api.replacement.to_owned()),
+          source: "request".to_string(),
+        }
+      }),
+  );
+
+  Ok(findings)
+}
+
+/// K8S012 - check if the manifest is written against an API removed at or before the target version
+pub(crate) fn manifest_removed_api(
+  deprecations: &BTreeMap<version::Version, Vec<DeprecatedApi>>,
+  manifest: &resources::Manifest,
+) -> Option<checks::RemovedApi> {
+  deprecations.iter().find_map(|(removed_in, apis)| {
+    apis
+      .iter()
+      .find(|api| api.api_version == manifest.api_version() && api.kind == manifest.kind())
+      .map(|api| checks::RemovedApi {
+        finding: new_finding(),
+        api_version: api.api_version.to_owned(),
+        kind: api.kind.to_owned(),
+        namespace: manifest.namespace().to_owned(),
+        name: manifest.name().to_owned(),
+        removed_in: *removed_in,
+        replacement: api.replacement.to_owned(),
+        source: manifest.path.to_owned(),
+      })
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn can_get_deprecated_apis() {
+    let deprecations = get_deprecated_apis(&version::Version::new(1, 25)).unwrap();
+
+    assert_eq!(
+      deprecations.keys().copied().collect::<Vec<_>>(),
+      vec![
+        version::Version::new(1, 16),
+        version::Version::new(1, 22),
+        version::Version::new(1, 25)
+      ]
+    );
+    assert!(deprecations
+      .values()
+      .flatten()
+      .any(|api| api.api_version == "extensions/v1beta1"
+        && api.kind == "Ingress"
+        && api.replacement.as_deref() == Some("networking.k8s.io/v1")));
+    assert_eq!(
+      deprecations[&version::Version::new(1, 25)][0].gvk(),
+      GroupVersionKind::gvk("batch", "v1beta1", "CronJob")
+    );
+  }
+
+  #[test]
+  fn can_parse_requested_apis() {
+    let metrics = r#"
+# HELP apiserver_requested_deprecated_apis [STABLE] Gauge of deprecated APIs that have been requested, broken out by API group, version, resource, subresource, and removed_release.
+# TYPE apiserver_requested_deprecated_apis gauge
+apiserver_requested_deprecated_apis{group="policy",removed_release="1.25",resource="podsecuritypolicies",subresource="",version="v1beta1"} 1
+apiserver_requested_deprecated_apis{group="autoscaling",removed_release="1.26",resource="horizontalpodautoscalers",subresource="",version="v2beta2"} 1
+apiserver_requested_deprecated_apis{group="example.com",removed_release="",resource="widgets",subresource="",version="v1alpha1"} 1
+apiserver_request_total{code="200",component="apiserver",group="",resource="pods",verb="LIST",version="v1"} 10
+"#;
+
+    assert_eq!(
+      parse_requested_apis(metrics),
+      vec![
+        RequestedApi {
+          api_version: "policy/v1beta1".to_string(),
+          resource: "podsecuritypolicies".to_string(),
+          removed_in: version::Version::new(1, 25),
+        },
+        RequestedApi {
+          api_version: "autoscaling/v2beta2".to_string(),
+          resource: "horizontalpodautoscalers".to_string(),
+          removed_in: version::Version::new(1, 26),
+        },
+      ]
+    );
+  }
+}
@@ -1,10 +1,20 @@
 use anyhow::Result;
+use aws_sdk_eks::types::Addon;
+use k8s_openapi::{
+  api::{core, policy},
+  chrono::Utc,
+};
 use kube::Client as K8sClient;
 use serde::{Deserialize, Serialize};
+use tracing::warn;
 
-use crate::k8s::{
-  checks::{self, K8sFindings},
-  resources,
+use crate::{
+  finding,
+  k8s::{
+    checks::{self, K8sFindings},
+    crds, deprecations, helm, resources, storage, webhooks,
+  },
+  version,
 };
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -14,31 +24,128 @@ pub struct KubernetesFindings {
   /// the skew policy could be violated post upgrade (i.e. if current skew is +2, the policy would be violated
   /// as soon as the control plane is upgraded, resulting in +3, and therefore changes are required before upgrade)
   pub version_skew: Vec<checks::VersionSkew>,
+  /// APIServices that are not available, reported alongside the cluster health issues
+  pub api_services: Vec<checks::ApiService>,
+  /// Nodes that are not ready, under pressure, or cordoned, as reported by Kubernetes
+  pub node_health: Vec<checks::NodeHealth>,
   pub min_replicas: Vec<checks::MinReplicas>,
   pub min_ready_seconds: Vec<checks::MinReadySeconds>,
-  pub readiness_probe: Vec<checks::Probe>,
+  pub pod_disruption_budget: Vec<checks::PodDisruptionBudget>,
+  /// PodDisruptionBudgets that prevent the pods they select from being evicted, causing node drains to fail
+  pub blocking_pod_disruption_budgets: Vec<checks::BlockingPodDisruptionBudget>,
+  pub probes: Vec<checks::Probe>,
   pub pod_topology_distribution: Vec<checks::PodTopologyDistribution>,
+  /// Workloads whose running replicas are all placed on a single node, nodegroup, or availability zone
+  pub pod_placement: Vec<checks::PodPlacement>,
+  /// Pods that are not managed by a controller, and will not be recreated once their node is drained
+  pub bare_pods: Vec<checks::BarePod>,
+  /// Pods that will block, or lose data on, node drains
+  pub eviction_blockers: Vec<checks::EvictionBlocker>,
   pub termination_grace_period: Vec<checks::TerminationGracePeriod>,
   pub docker_socket: Vec<checks::DockerSocket>,
   pub pod_security_policy: Vec<checks::PodSecurityPolicy>,
+  /// Admission webhooks that fail closed, and can prevent objects from being created while nodes are replaced
+  pub admission_webhooks: Vec<checks::AdmissionWebhook>,
+  /// In-tree Amazon EBS StorageClasses and PersistentVolumes, and the readiness of the Amazon EBS CSI driver
+  pub ebs_csi_migration: Vec<checks::EbsCsiMigration>,
+  /// StorageClasses provisioning `gp2` Amazon EBS volumes, with their volumes aggregated per namespace
+  pub gp2_volumes: Vec<checks::Gp2Volume>,
   pub kube_proxy_version_skew: Vec<checks::KubeProxyVersionSkew>,
+  /// Objects and requests using APIs that are removed at or before the target version
+  pub removed_apis: Vec<checks::RemovedApi>,
+  /// Helm release revisions whose stored manifests use APIs that are removed at or before the target version
+  pub helm_releases: Vec<checks::HelmRelease>,
+  /// CustomResourceDefinitions whose stored versions, conversion webhook, or schema will cause upgrades to fail
+  pub custom_resources: Vec<checks::CustomResource>,
+}
+
+/// Returns the resources requested, or `None` when they cannot be retrieved (i.e. - not permitted by RBAC)
+///
+/// The checks that depend on the resources are skipped rather than halting the analysis of the cluster
+fn skip_on_error<T>(result: Result<T>, resources: &str) -> Option<T> {
+  match result {
+    Ok(value) => Some(value),
+    Err(err) => {
+      warn!("Unable to list {resources}, skipping the checks that depend on them: {err}");
+      None
+    }
+  }
+}
+
+/// Findings derived from the pods running on the cluster
+#[derive(Default)]
+struct PodFindings {
+  blocking_pod_disruption_budgets: Vec<checks::BlockingPodDisruptionBudget>,
+  pod_placement: Vec<checks::PodPlacement>,
+  bare_pods: Vec<checks::BarePod>,
+  eviction_blockers: Vec<checks::EvictionBlocker>,
+}
+
+/// Returns the findings derived from the pods running on the cluster
+///
+/// The checks are skipped when the pods cannot be listed; an empty list of pods would otherwise report every
+/// podDisruptionBudget as selecting no pods
+fn get_pod_findings(
+  pods: Option<&[core::v1::Pod]>,
+  pdbs: Option<&[policy::v1::PodDisruptionBudget]>,
+  owners: Option<&resources::Owners>,
+  nodes: &[resources::Node],
+  resources: &[resources::StdResource],
+) -> PodFindings {
+  let Some(pods) = pods else {
+    return PodFindings::default();
+  };
+
+  PodFindings {
+    blocking_pod_disruption_budgets: pdbs
+      .map(|pdbs| checks::blocking_pod_disruption_budgets(pdbs, pods))
+      .unwrap_or_default(),
+    pod_placement: owners
+      .map(|owners| checks::pod_placement(pods, nodes, owners))
+      .unwrap_or_default(),
+    bare_pods: checks::bare_pods(pods),
+    eviction_blockers: checks::eviction_blockers(pods, resources, owners),
+  }
 }
 
 pub async fn get_kubernetes_findings(
   client: &K8sClient,
-  cluster_version: &str,
-  target_version: &str,
+  cluster_version: &version::Version,
+  target_version: &version::Version,
+  addons: &[Addon],
 ) -> Result<KubernetesFindings> {
   let resources = resources::get_resources(client).await?;
   let nodes = resources::get_nodes(client).await?;
 
-  let version_skew = checks::version_skew(&nodes, cluster_version).await?;
+  let version_skew = checks::version_skew(&nodes, cluster_version, target_version).await?;
+  let node_health = checks::node_health(&nodes, Utc::now());
+  let api_services = skip_on_error(resources::get_api_services(client).await, "APIServices")
+    .map(|api_services| checks::unavailable_api_services(&api_services))
+    .unwrap_or_default();
   let min_replicas: Vec<checks::MinReplicas> = resources.iter().filter_map(|s| s.min_replicas()).collect();
   let min_ready_seconds: Vec<checks::MinReadySeconds> =
     resources.iter().filter_map(|s| s.min_ready_seconds()).collect();
-  let pod_topology_distribution: Vec<checks::PodTopologyDistribution> =
-    resources.iter().filter_map(|s| s.pod_topology_distribution()).collect();
-  let readiness_probe: Vec<checks::Probe> = resources.iter().filter_map(|s| s.readiness_probe()).collect();
+  let pdbs = skip_on_error(
+    resources::get_pod_disruption_budgets(client).await,
+    "PodDisruptionBudgets",
+  );
+  let pod_disruption_budget: Vec<checks::PodDisruptionBudget> = pdbs
+    .as_ref()
+    .map(|pdbs| resources.iter().filter_map(|s| s.pod_disruption_budget(pdbs)).collect())
+    .unwrap_or_default();
+  let pods = skip_on_error(resources::get_pods(client).await, "pods");
+  let owners = skip_on_error(resources::get_owners(client).await, "ReplicaSets and Jobs");
+  let PodFindings {
+    blocking_pod_disruption_budgets,
+    pod_placement,
+    bare_pods,
+    eviction_blockers,
+  } = get_pod_findings(pods.as_deref(), pdbs.as_deref(), owners.as_ref(), &nodes, &resources);
+  let pod_topology_distribution: Vec<checks::PodTopologyDistribution> = resources
+    .iter()
+    .filter_map(|s| s.pod_topology_distribution(Some(nodes.len())))
+    .collect();
+  let probes: Vec<checks::Probe> = resources.iter().flat_map(|s| s.probes()).collect();
   let termination_grace_period: Vec<checks::TerminationGracePeriod> =
     resources.iter().filter_map(|s| s.termination_grace_period()).collect();
   let docker_socket: Vec<checks::DockerSocket> = resources
@@ -46,17 +153,197 @@ pub async fn get_kubernetes_findings(
     .filter_map(|s| s.docker_socket(target_version))
     .collect();
   let pod_security_policy = resources::get_podsecuritypolicies(client, target_version).await?;
+  let admission_webhooks = webhooks::admission_webhooks(client, &nodes).await?;
+  let storage_classes = skip_on_error(storage::get_storage_classes(client).await, "StorageClasses");
+  let pvs = skip_on_error(storage::get_persistent_volumes(client).await, "PersistentVolumes");
+  let (ebs_csi_migration, gp2_volumes) = match storage_classes.zip(pvs) {
+    Some((storage_classes, pvs)) => (
+      storage::get_ebs_csi_migration(client, &storage_classes, &pvs, addons, target_version).await?,
+      storage::gp2_volumes(&storage_classes, &pvs),
+    ),
+    None => (vec![], vec![]),
+  };
   let kube_proxy_version_skew = checks::kube_proxy_version_skew(&nodes, &resources).await?;
+  let removed_apis = deprecations::removed_apis(client, target_version).await?;
+  let helm_releases = helm::helm_releases(client, target_version).await?;
+  let custom_resources = crds::custom_resources(client).await?;
 
   Ok(KubernetesFindings {
     version_skew,
+    api_services,
+    node_health,
     min_replicas,
     min_ready_seconds,
-    readiness_probe,
+    pod_disruption_budget,
+    blocking_pod_disruption_budgets,
+    probes,
     pod_topology_distribution,
+    pod_placement,
+    bare_pods,
+    eviction_blockers,
     termination_grace_period,
     docker_socket,
     pod_security_policy,
+    admission_webhooks,
+    ebs_csi_migration,
+    gp2_volumes,
     kube_proxy_version_skew,
+    removed_apis,
+    helm_releases,
+    custom_resources,
   })
 }
+
+/// Findings from scanning Kubernetes manifests, outside of a cluster
+///
+/// Only the checks that can be determined from the manifests themselves are performed;
+/// checks that require the state of the cluster (i.e. - version skew) are not applicable
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ManifestFindings {
+  pub min_replicas: Vec<checks::MinReplicas>,
+  pub min_ready_seconds: Vec<checks::MinReadySeconds>,
+  pub probes: Vec<checks::Probe>,
+  pub pod_topology_distribution: Vec<checks::PodTopologyDistribution>,
+  pub termination_grace_period: Vec<checks::TerminationGracePeriod>,
+  pub docker_socket: Vec<checks::DockerSocket>,
+  pub removed_apis: Vec<checks::RemovedApi>,
+}
+
+impl ManifestFindings {
+  /// Returns all of the findings flattened into their common representation
+  pub(crate) fn entries(&self) -> Vec<finding::Entry> {
+    let mut entries = Vec::new();
+
+    entries.extend(finding::to_entries(&self.min_replicas));
+    entries.extend(finding::to_entries(&self.min_ready_seconds));
+    entries.extend(finding::to_entries(&self.pod_topology_distribution));
+    entries.extend(finding::to_entries(&self.probes));
+    entries.extend(finding::to_entries(&self.termination_grace_period));
+    entries.extend(finding::to_entries(&self.docker_socket));
+    entries.extend(finding::to_entries(&self.removed_apis));
+
+    entries
+  }
+}
+
+pub fn get_manifest_findings(
+  manifests: &[resources::Manifest],
+  target_version: &version::Version,
+) -> Result<ManifestFindings> {
+  let deprecations = deprecations::get_deprecated_apis(target_version)?;
+
+  let mut resources = Vec::new();
+  for manifest in manifests {
+    // A manifest that cannot be read as its resource type is skipped rather than halting the scan
+    match resources::get_manifest_resource(manifest) {
+      Ok(Some(resource)) => resources.push(resource),
+      Ok(None) => {}
+      Err(err) => warn!(
+        "Skipping {} {} in {}: {err}",
+        manifest.kind(),
+        manifest.name(),
+        manifest.path
+      ),
+    }
+  }
+
+  Ok(ManifestFindings {
+    min_replicas: resources.iter().filter_map(|s| s.min_replicas()).collect(),
+    min_ready_seconds: resources.iter().filter_map(|s| s.min_ready_seconds()).collect(),
+    probes: resources.iter().flat_map(|s| s.probes()).collect(),
+    pod_topology_distribution: resources
+      .iter()
+      .filter_map(|s| s.pod_topology_distribution(None))
+      .collect(),
+    termination_grace_period: resources.iter().filter_map(|s| s.termination_grace_period()).collect(),
+    docker_socket: resources
+      .iter()
+      .filter_map(|s| s.docker_socket(target_version))
+      .collect(),
+    removed_apis: manifests
+      .iter()
+      .filter_map(|manifest| deprecations::manifest_removed_api(&deprecations, manifest))
+      .collect(),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn can_skip_pod_findings() {
+    let pdbs: Vec<policy::v1::PodDisruptionBudget> = serde_yaml::from_str(
+      "- metadata: {name: web, namespace: default}\n  spec: {selector: {matchLabels: {app: web}}, maxUnavailable: 1}",
+    )
+    .unwrap();
+    let pod: core::v1::Pod = serde_yaml::from_str("metadata: {name: debug, namespace: default}").unwrap();
+    let owners = resources::Owners::new();
+
+    // Listing the pods failed, the checks that depend on them are skipped
+    let findings = get_pod_findings(None, Some(&pdbs), Some(&owners), &[], &[]);
+    assert!(findings.blocking_pod_disruption_budgets.is_empty());
+    assert!(findings.pod_placement.is_empty());
+    assert!(findings.bare_pods.is_empty());
+    assert!(findings.eviction_blockers.is_empty());
+
+    let findings = get_pod_findings(Some(&[pod]), Some(&pdbs), Some(&owners), &[], &[]);
+    assert_eq!(findings.blocking_pod_disruption_budgets[0].reason, "Selects no pods");
+    assert_eq!(findings.bare_pods.len(), 1);
+
+    // Listing the owners failed, the pods are still checked for eviction blockers
+    let pod: core::v1::Pod = serde_yaml::from_str(
+      "metadata: {name: debug, namespace: default, annotations: {karpenter.sh/do-not-disrupt: 'true'}}",
+    )
+    .unwrap();
+    let findings = get_pod_findings(Some(&[pod]), None, None, &[], &[]);
+    assert!(findings.pod_placement.is_empty());
+    assert_eq!(findings.eviction_blockers.len(), 1);
+  }
+
+  #[test]
+  fn can_get_manifest_entries() {
+    let contents = r#"
+apiVersion: apps/v1beta2
+kind: Deployment
+metadata:
+  name: web
+  namespace: default
+spec:
+  replicas: 1
+  selector:
+    matchLabels:
+      app: web
+  template:
+    metadata:
+      labels:
+        app: web
+    spec:
+      containers:
+        - name: web
+          image: nginx
+"#;
+    let manifests = resources::Manifest::parse("-", contents).unwrap();
+    let findings = get_manifest_findings(&manifests, &version::Version::new(1, 25)).unwrap();
+    let entries = findings.entries();
+
+    // Every finding is represented by exactly one entry
+    let count = findings.min_replicas.len()
+      + findings.min_ready_seconds.len()
+      + findings.pod_topology_distribution.len()
+      + findings.probes.len()
+      + findings.termination_grace_period.len()
+      + findings.docker_socket.len()
+      + findings.removed_apis.len();
+    assert_eq!(entries.len(), count);
+
+    let min_replicas = entries.iter().find(|entry| entry.code.to_string() == "K8S002").unwrap();
+    assert_eq!(min_replicas.api_version.as_deref(), Some("apps/v1"));
+    assert_eq!(min_replicas.kind, "Deployment");
+    assert_eq!(min_replicas.namespace.as_deref(), Some("default"));
+    assert_eq!(min_replicas.name, "web");
+
+    let removed_api = entries.iter().find(|entry| entry.code.to_string() == "K8S012").unwrap();
+    assert_eq!(removed_api.remediation.to_string(), "Required");
+  }
+}
@@ -0,0 +1,228 @@
+use std::{collections::BTreeMap, io::Read};
+
+use anyhow::Result;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use k8s_openapi::api::core;
+use kube::{
+  api::{Api, ListParams},
+  Client,
+};
+use libflate::gzip;
+use serde::Deserialize;
+use tracing::warn;
+
+use crate::{
+  finding,
+  k8s::{checks, deprecations, resources},
+  version,
+};
+
+/// Label selector for the Secrets Helm uses to store releases
+const OWNER_SELECTOR: &str = "owner=helm";
+
+/// Type of the Secrets Helm v3 uses to store releases (`sh.helm.release.v1.<release>.v<revision>`)
+const RELEASE_SECRET_TYPE: &str = "helm.sh/release.v1";
+
+/// Magic number of gzip compressed data
+const GZIP_HEADER: [u8; 2] = [0x1f, 0x8b];
+
+/// The subset of a Helm release (revision) relevant to the checks
+#[derive(Debug, Deserialize)]
+struct Release {
+  name: String,
+  namespace: String,
+  version: u32,
+  #[serde(default)]
+  manifest: String,
+  info: Option<ReleaseInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReleaseInfo {
+  status: String,
+}
+
+/// Decode the release stored in a Helm release Secret
+///
+/// Helm stores the release as gzip compressed JSON that is base64 encoded, on top
+/// of the base64 encoding of the Secret data itself (decoded by the API client)
+fn decode_release(data: &[u8]) -> Result<Release> {
+  let decoded = STANDARD.decode(data)?;
+
+  let json = if decoded.starts_with(&GZIP_HEADER) {
+    let mut decompressed = Vec::new();
+    gzip::Decoder::new(decoded.as_slice())?.read_to_end(&mut decompressed)?;
+    decompressed
+  } else {
+    decoded
+  };
+
+  Ok(serde_json::from_slice(&json)?)
+}
+
+/// Returns the Helm releases stored in the cluster, across all revisions retained in the release history
+async fn get_releases(client: &Client) -> Result<Vec<Release>> {
+  let api: Api<core::v1::Secret> = Api::all(client.to_owned());
+  let secret_list = api.list(&ListParams::default().labels(OWNER_SELECTOR)).await?;
+
+  Ok(
+    secret_list
+      .items
+      .iter()
+      .filter(|secret| secret.type_.as_deref() == Some(RELEASE_SECRET_TYPE))
+      .filter_map(|secret| {
+        let name = secret.metadata.name.as_deref().unwrap_or_default();
+        let data = secret.data.as_ref().and_then(|data| data.get("release"))?;
+
+        // A release that cannot be decoded is skipped rather than halting the analysis of the cluster
+        match decode_release(&data.0) {
+          Ok(release) => Some(release),
+          Err(err) => {
+            warn!("Skipping Helm release {name}: {err}");
+            None
+          }
+        }
+      })
+      .collect(),
+  )
+}
+
+/// K8S013 - check if the manifests stored in the Helm release history contain APIs removed at or before the target version
+///
+/// Remediation is required when the latest revision of a release contains removed APIs since the release
+/// can no longer be upgraded, and recommended for prior revisions since they can no longer be rolled back to
+fn check_releases(releases: &[Release], target_version: &version::Version) -> Result<Vec<checks::HelmRelease>> {
+  let deprecations = deprecations::get_deprecated_apis(target_version)?;
+
+  let mut latest: BTreeMap<(&str, &str), u32> = BTreeMap::new();
+  for release in releases {
+    let revision = latest.entry((&release.namespace, &release.name)).or_default();
+    *revision = (*revision).max(release.version);
+  }
+
+  let mut findings = Vec::new();
+  for release in releases {
+    let manifests = match resources::Manifest::parse(&release.name, &release.manifest) {
+      Ok(manifests) => manifests,
+      Err(err) => {
+        warn!("Skipping Helm release {} v{}: {err}", release.name, release.version);
+        continue;
+      }
+    };
+
+    let removed_apis: Vec<String> = manifests
+      .iter()
+      .filter_map(|manifest| deprecations::manifest_removed_api(&deprecations, manifest))
+      .map(|removed| format!("{} {}/{}", removed.api_version, removed.kind, removed.name))
+      .collect();
+    if removed_apis.is_empty() {
+      continue;
+    }
+
+    let remediation = if latest.get(&(release.namespace.as_str(), release.name.as_str())) == Some(&release.version) {
+      finding::Remediation::Required
+    } else {
+      finding::Remediation::Recommended
+    };
+    let finding = finding::Finding {
+      code: finding::Code::K8S013,
+      symbol: remediation.symbol(),
+      remediation,
+    };
+
+    findings.push(checks::HelmRelease {
+      finding,
+      release: release.name.to_owned(),
+      namespace: release.namespace.to_owned(),
+      revision: release.version,
+      status: release
+        .info
+        .as_ref()
+        .map(|info| info.status.to_owned())
+        .unwrap_or_default(),
+      removed_apis,
+    });
+  }
+  findings.sort_by(|a, b| (&a.namespace, &a.release, a.revision).cmp(&(&b.namespace, &b.release, b.revision)));
+
+  Ok(findings)
+}
+
+/// K8S013 - check the Helm release history for APIs removed at or before the target version
+pub(crate) async fn helm_releases(
+  client: &Client,
+  target_version: &version::Version,
+) -> Result<Vec<checks::HelmRelease>> {
+  let releases = match get_releases(client).await {
+    Ok(releases) => releases,
+    Err(err) => {
+      warn!("Unable to list Helm release secrets: {err}");
+      return Ok(vec![]);
+    }
+  };
+
+  check_releases(&releases, target_version)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::io::Write;
+
+  use super::*;
+
+  fn release(version: u32, manifest: &str) -> Release {
+    Release {
+      name: "example".to_string(),
+      namespace: "default".to_string(),
+      version,
+      manifest: manifest.to_string(),
+      info: Some(ReleaseInfo {
+        status: "superseded".to_string(),
+      }),
+    }
+  }
+
+  #[test]
+  fn can_decode_release() {
+    let json = r#"{"name":"example","namespace":"default","version":2,"manifest":"---\nkind: Service","info":{"status":"deployed"}}"#;
+
+    let mut encoder = gzip::Encoder::new(Vec::new()).unwrap();
+    encoder.write_all(json.as_bytes()).unwrap();
+    let compressed = encoder.finish().into_result().unwrap();
+
+    for data in [compressed, json.as_bytes().to_vec()] {
+      let release = decode_release(STANDARD.encode(data).as_bytes()).unwrap();
+      assert_eq!(release.name, "example");
+      assert_eq!(release.version, 2);
+      assert_eq!(release.info.unwrap().status, "deployed");
+    }
+  }
+
+  #[test]
+  fn can_check_releases() {
+    let ingress = "---\napiVersion: networking.k8s.io/v1beta1\nkind: Ingress\nmetadata:\n  name: web\n";
+    let releases = vec![
+      release(1, ingress),
+      release(2, ingress),
+      release(3, &ingress.replace("v1beta1", "v1")),
+    ];
+
+    let findings = check_releases(&releases, &version::Version::new(1, 22)).unwrap();
+    let revisions: Vec<(u32, String)> = findings
+      .iter()
+      .map(|f| (f.revision, f.finding.remediation.to_string()))
+      .collect();
+    assert_eq!(
+      revisions,
+      vec![(1, "Recommended".to_string()), (2, "Recommended".to_string())]
+    );
+    assert_eq!(findings[0].removed_apis, vec!["networking.k8s.io/v1beta1 Ingress/web"]);
+
+    let findings = check_releases(&releases[..2], &version::Version::new(1, 22)).unwrap();
+    assert_eq!(findings[1].finding.remediation.to_string(), "Required");
+
+    assert!(check_releases(&releases, &version::Version::new(1, 21))
+      .unwrap()
+      .is_empty());
+  }
+}
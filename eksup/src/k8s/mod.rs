@@ -1,6 +1,15 @@
 mod checks;
+mod crds;
+mod deprecations;
 mod findings;
+mod helm;
+mod report;
 mod resources;
+mod storage;
+mod webhooks;
 
-pub use findings::{get_kubernetes_findings, KubernetesFindings};
-pub use resources::get_eniconfigs;
+pub(crate) use deprecations::{get_apis_removed_after, split_api_version};
+pub use findings::{get_kubernetes_findings, get_manifest_findings, KubernetesFindings, ManifestFindings};
+pub use report::{emit_events, get_report_crd, write_reports, UpgradeReadinessReportSpec};
+pub use resources::{get_eniconfigs, Manifest};
+pub use storage::get_gp3_manifests;
@@ -0,0 +1,400 @@
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::Result;
+use k8s_openapi::{
+  api::core::v1::{Event, EventSource, ObjectReference},
+  apimachinery::pkg::apis::meta::v1::{ObjectMeta, Time},
+  chrono::Utc,
+};
+use kube::{
+  api::{Api, ApiResource, DynamicObject, GroupVersionKind, ListParams, Patch, PatchParams, PostParams},
+  discovery, Client, CustomResource, CustomResourceExt, Resource,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::finding;
+
+/// Field manager used when applying the reports to the cluster
+const FIELD_MANAGER: &str = "eksup";
+
+/// Label applied to reports to identify the cluster the report belongs to
+const CLUSTER_LABEL: &str = "eksup.clowdhaus.github.io/cluster";
+
+/// Custom resource definition for the upgrade readiness report written by `eksup`
+///
+/// A report is written for the cluster as a whole into the namespace `eksup` runs in, along with
+/// a report in each namespace that contains Kubernetes resources with findings. This allows users
+/// that only have access to their own namespace(s) to view the findings relevant to them with
+/// `kubectl get upgradereadinessreports`
+#[derive(Clone, CustomResource, Debug, Default, Deserialize, JsonSchema, Serialize)]
+#[kube(
+  group = "eksup.clowdhaus.github.io",
+  kind = "UpgradeReadinessReport",
+  namespaced,
+  plural = "upgradereadinessreports",
+  singular = "upgradereadinessreport",
+  shortname = "urr",
+  status = "UpgradeReadinessReportStatus",
+  version = "v1alpha1",
+  printcolumn = r#"{"name":"Current", "type":"string", "jsonPath":".spec.currentVersion"}"#,
+  printcolumn = r#"{"name":"Target", "type":"string", "jsonPath":".spec.targetVersion"}"#,
+  printcolumn = r#"{"name":"Required", "type":"integer", "jsonPath":".status.summary.required"}"#,
+  printcolumn = r#"{"name":"Recommended", "type":"integer", "jsonPath":".status.summary.recommended"}"#,
+  printcolumn = r#"{"name":"Updated", "type":"date", "jsonPath":".status.lastUpdated"}"#
+)]
+#[serde(rename_all = "camelCase")]
+pub struct UpgradeReadinessReportSpec {
+  pub cluster_name: String,
+  pub current_version: String,
+  pub target_version: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, JsonSchema, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpgradeReadinessReportStatus {
+  /// Time the report was last written (RFC 3339)
+  pub last_updated: String,
+  pub summary: finding::Summary,
+  pub findings: Vec<finding::Entry>,
+}
+
+/// Returns the custom resource definition of the report as YAML
+pub fn get_report_crd() -> Result<String> {
+  Ok(serde_yaml::to_string(&UpgradeReadinessReport::crd())?)
+}
+
+/// Returns the cluster name as a valid object name (RFC 1123 subdomain) for the report
+///
+/// Amazon EKS cluster names may contain uppercase letters and underscores, which object names do not permit;
+/// the original cluster name is retained in the spec of the report
+fn report_name(cluster_name: &str) -> String {
+  cluster_name.to_lowercase().replace('_', "-")
+}
+
+/// Returns the cluster name as a valid label value, which is limited to 63 characters
+fn cluster_label(cluster_name: &str) -> String {
+  let mut label = report_name(cluster_name);
+  label.truncate(63);
+
+  label.trim_end_matches(['-', '.']).to_owned()
+}
+
+/// Create or update a report in the given namespace
+async fn apply_report(
+  client: &Client,
+  namespace: &str,
+  spec: &UpgradeReadinessReportSpec,
+  findings: Vec<finding::Entry>,
+) -> Result<()> {
+  let api: Api<UpgradeReadinessReport> = Api::namespaced(client.to_owned(), namespace);
+  let params = PatchParams::apply(FIELD_MANAGER).force();
+
+  let name = report_name(&spec.cluster_name);
+  let mut report = UpgradeReadinessReport::new(&name, spec.to_owned());
+  report.metadata.labels = Some(BTreeMap::from([(
+    CLUSTER_LABEL.to_string(),
+    cluster_label(&spec.cluster_name),
+  )]));
+  api.patch(&name, &params, &Patch::Apply(&report)).await?;
+
+  let status = serde_json::json!({
+    "apiVersion": UpgradeReadinessReport::api_version(&()),
+    "kind": UpgradeReadinessReport::kind(&()),
+    "status": UpgradeReadinessReportStatus {
+      last_updated: Utc::now().to_rfc3339(),
+      summary: finding::Summary::new(&findings),
+      findings,
+    },
+  });
+  api.patch_status(&name, &params, &Patch::Apply(&status)).await?;
+
+  debug!("Wrote upgrade readiness report {namespace}/{name}");
+  Ok(())
+}
+
+/// Write the findings into the cluster as `UpgradeReadinessReport` custom resources
+///
+/// The report in `namespace` contains all findings for the cluster, and a report is written into
+/// each namespace that contains resources with findings, scoped to only the findings of that namespace.
+/// Reports from prior runs in namespaces that no longer have findings are updated to reflect that
+pub async fn write_reports(
+  client: &Client,
+  namespace: &str,
+  spec: &UpgradeReadinessReportSpec,
+  entries: &[finding::Entry],
+) -> Result<()> {
+  apply_report(client, namespace, spec, entries.to_vec()).await?;
+
+  let mut namespaced: BTreeMap<String, Vec<finding::Entry>> = BTreeMap::new();
+  for entry in entries {
+    if let Some(ns) = &entry.namespace {
+      if ns != namespace {
+        namespaced.entry(ns.to_owned()).or_default().push(entry.to_owned());
+      }
+    }
+  }
+
+  // Namespaces with reports from a prior run whose findings have since been resolved
+  let api: Api<UpgradeReadinessReport> = Api::all(client.to_owned());
+  let params = ListParams::default().labels(&format!("{CLUSTER_LABEL}={}", cluster_label(&spec.cluster_name)));
+  let existing: HashSet<String> = api
+    .list(&params)
+    .await?
+    .iter()
+    .filter_map(|report| report.metadata.namespace.to_owned())
+    .filter(|ns| ns != namespace)
+    .collect();
+  for ns in existing {
+    namespaced.entry(ns).or_default();
+  }
+
+  for (ns, findings) in namespaced {
+    apply_report(client, &ns, spec, findings).await?;
+  }
+
+  Ok(())
+}
+
+/// Returns the name of the event for the finding, which is stable across runs so that the event of a
+/// finding that is reported again is updated rather than a new event created
+fn event_name(entry: &finding::Entry) -> String {
+  format!("{}.{}.{}", entry.name, entry.kind, entry.code).to_lowercase()
+}
+
+/// Returns a new event for the finding, reported on the resource in the namespace provided
+fn new_event(entry: &finding::Entry, namespace: &str, uid: Option<String>, now: &Time) -> Event {
+  let code = entry.code.to_string();
+
+  Event {
+    metadata: ObjectMeta {
+      name: Some(event_name(entry)),
+      namespace: Some(namespace.to_owned()),
+      ..ObjectMeta::default()
+    },
+    involved_object: ObjectReference {
+      api_version: entry.api_version.to_owned(),
+      kind: Some(entry.kind.to_owned()),
+      name: Some(entry.name.to_owned()),
+      namespace: Some(namespace.to_owned()),
+      uid,
+      ..ObjectReference::default()
+    },
+    reason: Some(code.to_owned()),
+    message: Some(format!(
+      "{} upgrade readiness finding {code}: https://clowdhaus.github.io/eksup/info/checks/#{}",
+      entry.remediation,
+      code.to_lowercase()
+    )),
+    type_: Some("Warning".to_string()),
+    source: Some(EventSource {
+      component: Some(FIELD_MANAGER.to_string()),
+      ..EventSource::default()
+    }),
+    reporting_component: Some(FIELD_MANAGER.to_string()),
+    first_timestamp: Some(now.to_owned()),
+    last_timestamp: Some(now.to_owned()),
+    count: Some(1),
+    ..Event::default()
+  }
+}
+
+/// Returns the uid of the resource the finding is reported on, which `kubectl describe` uses to find its events
+///
+/// The resources are looked up by listing with a field selector on their name, which only requires the
+/// `list` permission already granted for the checks. API resources resolved through discovery are cached
+async fn get_uid(
+  client: &Client,
+  api_resources: &mut BTreeMap<(String, String), Option<ApiResource>>,
+  api_version: &str,
+  kind: &str,
+  namespace: &str,
+  name: &str,
+) -> Result<Option<String>> {
+  let key = (api_version.to_owned(), kind.to_owned());
+  if !api_resources.contains_key(&key) {
+    let (group, version) = api_version.split_once('/').unwrap_or(("", api_version));
+    let resource = discovery::pinned_kind(client, &GroupVersionKind::gvk(group, version, kind))
+      .await
+      .map(|(resource, _)| resource)
+      .ok();
+    api_resources.insert(key.to_owned(), resource);
+  }
+  let resource = match &api_resources[&key] {
+    Some(resource) => resource,
+    None => return Ok(None),
+  };
+
+  let api: Api<DynamicObject> = Api::namespaced_with(client.to_owned(), namespace, resource);
+  let params = ListParams::default().fields(&format!("metadata.name={name}"));
+  let list = api.list_metadata(&params).await?;
+
+  Ok(list.items.into_iter().next().and_then(|object| object.metadata.uid))
+}
+
+/// Returns the findings that events are created for, along with the namespace and API version of their resource
+///
+/// Only findings reported on namespaced Kubernetes resources are able to have events created. Findings with the
+/// same code on the same resource (i.e. - K8S006 reported per container) share an event and are reported once
+fn event_entries(entries: &[finding::Entry]) -> Vec<(&str, &str, &finding::Entry)> {
+  let mut seen = HashSet::new();
+
+  entries
+    .iter()
+    .filter_map(|entry| match (&entry.namespace, &entry.api_version) {
+      (Some(namespace), Some(api_version)) => Some((namespace.as_str(), api_version.as_str(), entry)),
+      _ => None,
+    })
+    .filter(|(namespace, _, entry)| seen.insert((namespace.to_owned(), event_name(entry))))
+    .collect()
+}
+
+/// Create the event for the finding, or update the event that already exists for it from a prior run
+async fn emit_event(
+  api: &Api<Event>,
+  entry: &finding::Entry,
+  namespace: &str,
+  uid: Option<String>,
+  now: &Time,
+) -> Result<()> {
+  let name = event_name(entry);
+  match api.get_opt(&name).await? {
+    Some(existing) => {
+      let patch = serde_json::json!({
+        "count": existing.count.unwrap_or(1) + 1,
+        "lastTimestamp": now,
+      });
+      api.patch(&name, &PatchParams::default(), &Patch::Merge(&patch)).await?;
+    }
+    None => {
+      api
+        .create(&PostParams::default(), &new_event(entry, namespace, uid, now))
+        .await?;
+    }
+  }
+
+  Ok(())
+}
+
+/// Create or update a Kubernetes event on each resource that has a reported finding
+///
+/// An event that already exists for the finding from a prior run is updated, incrementing its count.
+/// Events that cannot be written (i.e. - not permitted in the namespace) are skipped rather than halting the run
+pub async fn emit_events(client: &Client, entries: &[finding::Entry]) -> Result<()> {
+  let now = Time(Utc::now());
+  let mut api_resources = BTreeMap::new();
+
+  for (namespace, api_version, entry) in event_entries(entries) {
+    // The event is still created when the resource cannot be looked up, it is just not linked to the resource uid
+    let uid = match get_uid(
+      client,
+      &mut api_resources,
+      api_version,
+      &entry.kind,
+      namespace,
+      &entry.name,
+    )
+    .await
+    {
+      Ok(uid) => uid,
+      Err(err) => {
+        debug!(
+          "Unable to get the uid of {} {namespace}/{}: {err}",
+          entry.kind, entry.name
+        );
+        None
+      }
+    };
+
+    let api: Api<Event> = Api::namespaced(client.to_owned(), namespace);
+    if let Err(err) = emit_event(&api, entry, namespace, uid, &now).await {
+      warn!(
+        "Unable to write the {} event of {} {namespace}/{}: {err}",
+        entry.code, entry.kind, entry.name
+      );
+    }
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn can_create_event() {
+    let entry = finding::Entry {
+      api_version: Some("apps/v1".to_string()),
+      namespace: Some("default".to_string()),
+      ..finding::Entry::new(
+        &finding::Finding {
+          code: finding::Code::K8S002,
+          symbol: finding::Remediation::Required.symbol(),
+          remediation: finding::Remediation::Required,
+        },
+        "Deployment",
+        "web",
+      )
+    };
+
+    let now = Time(Utc::now());
+    let event = new_event(&entry, "default", Some("1234".to_string()), &now);
+    assert_eq!(event.metadata.name.as_deref(), Some("web.deployment.k8s002"));
+    assert_eq!(event.involved_object.uid.as_deref(), Some("1234"));
+    assert_eq!(event.involved_object.kind.as_deref(), Some("Deployment"));
+    assert_eq!(event.reason.as_deref(), Some("K8S002"));
+    // The same finding reported in a later run resolves to the same event
+    assert_eq!(event_name(&entry), event.metadata.name.unwrap());
+  }
+
+  #[test]
+  fn can_name_report() {
+    assert_eq!(report_name("My_Cluster-01"), "my-cluster-01");
+    assert_eq!(cluster_label("My_Cluster-01"), "my-cluster-01");
+
+    // EKS cluster names may be up to 100 characters
+    let long = format!("{}_{}", "a".repeat(62), "b".repeat(37));
+    assert_eq!(report_name(&long).len(), 100);
+    assert_eq!(cluster_label(&long), "a".repeat(62));
+  }
+
+  #[test]
+  fn can_deduplicate_event_entries() {
+    let entry = |code: finding::Code, name: &str, namespace: Option<&str>| finding::Entry {
+      api_version: Some("apps/v1".to_string()),
+      namespace: namespace.map(String::from),
+      ..finding::Entry::new(
+        &finding::Finding {
+          code,
+          symbol: finding::Remediation::Recommended.symbol(),
+          remediation: finding::Remediation::Recommended,
+        },
+        "Deployment",
+        name,
+      )
+    };
+
+    let entries = vec![
+      // Reported per container
+      entry(finding::Code::K8S006, "web", Some("default")),
+      entry(finding::Code::K8S006, "web", Some("default")),
+      entry(finding::Code::K8S002, "web", Some("default")),
+      entry(finding::Code::K8S006, "web", Some("other")),
+      entry(finding::Code::K8S006, "web", None),
+    ];
+    let events: Vec<(&str, String)> = event_entries(&entries)
+      .into_iter()
+      .map(|(namespace, _, entry)| (namespace, event_name(entry)))
+      .collect();
+    assert_eq!(
+      events,
+      vec![
+        ("default", "web.deployment.k8s006".to_string()),
+        ("default", "web.deployment.k8s002".to_string()),
+        ("other", "web.deployment.k8s006".to_string()),
+      ]
+    );
+  }
+}
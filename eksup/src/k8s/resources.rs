@@ -1,15 +1,23 @@
-use std::collections::BTreeMap;
-
-use anyhow::Result;
-use k8s_openapi::api::{
-  apps, batch,
-  core::{self, v1::PodTemplateSpec},
-  policy,
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{Context, Result};
+use k8s_openapi::{
+  api::{
+    apps, batch,
+    core::{self, v1::PodTemplateSpec},
+    policy,
+  },
+  apimachinery::pkg::apis::meta::v1::{LabelSelector, ObjectMeta, OwnerReference, Time},
+  kube_aggregator::pkg::apis::apiregistration::v1::APIService,
+};
+use kube::{
+  api::{Api, ApiResource, DynamicObject, GroupVersionKind},
+  Client, CustomResource,
 };
-use kube::{api::Api, Client, CustomResource};
 use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use tabled::Tabled;
+use tracing::warn;
 
 use crate::{finding, k8s::checks, version};
 
@@ -44,6 +52,10 @@ pub enum Kind {
   StatefulSet,
   CronJob,
   Job,
+  /// Argo Rollouts `Rollout`
+  Rollout,
+  /// OpenKruise `CloneSet`
+  CloneSet,
 }
 
 impl std::fmt::Display for Kind {
@@ -57,6 +69,22 @@ impl std::fmt::Display for Kind {
       Kind::StatefulSet => write!(f, "StatefulSet"),
       Kind::CronJob => write!(f, "CronJob"),
       Kind::Job => write!(f, "Job"),
+      Kind::Rollout => write!(f, "Rollout"),
+      Kind::CloneSet => write!(f, "CloneSet"),
+    }
+  }
+}
+
+impl Kind {
+  /// The API version used to access the resource kind
+  pub fn api_version(&self) -> &'static str {
+    match *self {
+      Kind::DaemonSet | Kind::Deployment | Kind::ReplicaSet | Kind::StatefulSet => "apps/v1",
+      Kind::PodSecurityPolicy => "policy/v1beta1",
+      Kind::ReplicationController => "v1",
+      Kind::CronJob | Kind::Job => "batch/v1",
+      Kind::Rollout => "argoproj.io/v1alpha1",
+      Kind::CloneSet => "apps.kruise.io/v1alpha1",
     }
   }
 }
@@ -66,7 +94,14 @@ pub struct Node {
   pub name: String,
   pub labels: Option<BTreeMap<String, String>>,
   pub kubelet_version: String,
-  pub minor_version: i32,
+  pub version: version::Version,
+  /// Status of the node conditions, keyed by their type (i.e. - `Ready`, `MemoryPressure`)
+  pub conditions: BTreeMap<String, String>,
+  /// Set when the node is cordoned
+  pub unschedulable: bool,
+  /// Taints of the node, in the form of `<key>=<value>:<effect>`
+  pub taints: Vec<String>,
+  pub created: Option<Time>,
 }
 
 pub async fn get_nodes(client: &Client) -> Result<Vec<Node>> {
@@ -76,18 +111,53 @@ pub async fn get_nodes(client: &Client) -> Result<Vec<Node>> {
   Ok(
     node_list
       .iter()
-      .map(|node| {
-        let status = node.status.as_ref().unwrap();
-        let node_info = status.node_info.as_ref().unwrap();
-        let kubelet_version = node_info.kubelet_version.to_owned();
-        let minor_version = version::parse_minor(&kubelet_version).unwrap();
-
-        Node {
-          name: node.metadata.name.as_ref().unwrap().to_owned(),
+      .filter_map(|node| {
+        let name = node.metadata.name.to_owned().unwrap_or_default();
+        let kubelet_version = node
+          .status
+          .as_ref()
+          .and_then(|status| status.node_info.as_ref())
+          .map(|node_info| node_info.kubelet_version.to_owned())
+          .unwrap_or_default();
+
+        // A node with an unexpected version is skipped rather than halting the analysis of the cluster
+        let version = match kubelet_version.parse() {
+          Ok(version) => version,
+          Err(err) => {
+            warn!("Skipping node {name}: {err}");
+            return None;
+          }
+        };
+
+        let spec = node.spec.to_owned().unwrap_or_default();
+        let conditions = node
+          .status
+          .as_ref()
+          .and_then(|status| status.conditions.as_ref())
+          .into_iter()
+          .flatten()
+          .map(|condition| (condition.type_.to_owned(), condition.status.to_owned()))
+          .collect();
+        let taints = spec
+          .taints
+          .iter()
+          .flatten()
+          .map(|taint| match &taint.value {
+            Some(value) => format!("{}={value}:{}", taint.key, taint.effect),
+            None => format!("{}:{}", taint.key, taint.effect),
+          })
+          .collect();
+
+        Some(Node {
+          name,
           labels: node.metadata.labels.to_owned(),
           kubelet_version,
-          minor_version,
-        }
+          version,
+          conditions,
+          unschedulable: spec.unschedulable.unwrap_or(false),
+          taints,
+          created: node.metadata.creation_timestamp.to_owned(),
+        })
       })
       .collect(),
   )
@@ -104,252 +174,531 @@ pub async fn get_eniconfigs(client: &Client) -> Result<Vec<ENIConfig>> {
   Ok(eniconfigs)
 }
 
+/// Standard metadata of the resource, of the kind provided
+fn get_metadata(objmeta: &ObjectMeta, kind: Kind) -> StdMetadata {
+  let owner = get_controller(objmeta).map(|owner| format!("{}/{}", owner.kind, owner.name));
+  let objmeta = objmeta.clone();
+
+  StdMetadata {
+    name: objmeta.name.unwrap_or_default(),
+    namespace: objmeta.namespace.unwrap_or_default(),
+    kind,
+    labels: objmeta.labels.unwrap_or_default(),
+    annotations: objmeta.annotations.unwrap_or_default(),
+    owner,
+  }
+}
+
+/// Kinds of the workloads that are inspected, including the custom workload types in `CUSTOM_WORKLOADS`
+const WORKLOAD_KINDS: [&str; 9] = [
+  "CronJob",
+  "DaemonSet",
+  "Deployment",
+  "Job",
+  "ReplicaSet",
+  "ReplicationController",
+  "StatefulSet",
+  "Rollout",
+  "CloneSet",
+];
+
+/// Returns the resource with its top-level controller as the owner, or `None` when the top-level controller is
+/// itself an inspected workload, in which case the findings are reported on the controller instead (i.e. - a
+/// ReplicaSet managed by a Deployment, or a Job managed by a CronJob)
+fn unless_managed(mut resource: StdResource, metadata: &ObjectMeta, owners: &Owners) -> Option<StdResource> {
+  match get_top_level_controller(metadata, owners) {
+    Some(owner) if WORKLOAD_KINDS.contains(&owner.kind.as_str()) => None,
+    owner => {
+      resource.metadata.owner = owner.map(|owner| format!("{}/{}", owner.kind, owner.name));
+      Some(resource)
+    }
+  }
+}
+
+impl From<&apps::v1::Deployment> for StdResource {
+  fn from(dplmnt: &apps::v1::Deployment) -> Self {
+    let metadata = get_metadata(&dplmnt.metadata, Kind::Deployment);
+    let spec = match &dplmnt.spec {
+      Some(spec) => StdSpec {
+        min_ready_seconds: spec.min_ready_seconds,
+        replicas: spec.replicas,
+        template: Some(spec.template.clone()),
+      },
+      None => StdSpec {
+        min_ready_seconds: None,
+        replicas: None,
+        template: None,
+      },
+    };
+
+    StdResource { metadata, spec }
+  }
+}
+
+impl From<&apps::v1::ReplicaSet> for StdResource {
+  fn from(repl: &apps::v1::ReplicaSet) -> Self {
+    let metadata = get_metadata(&repl.metadata, Kind::ReplicaSet);
+    let spec = match &repl.spec {
+      Some(spec) => StdSpec {
+        min_ready_seconds: spec.min_ready_seconds,
+        replicas: spec.replicas,
+        template: spec.template.clone(),
+      },
+      None => StdSpec {
+        min_ready_seconds: None,
+        replicas: None,
+        template: None,
+      },
+    };
+
+    StdResource { metadata, spec }
+  }
+}
+
+impl From<&core::v1::ReplicationController> for StdResource {
+  fn from(rc: &core::v1::ReplicationController) -> Self {
+    let metadata = get_metadata(&rc.metadata, Kind::ReplicationController);
+    let spec = match &rc.spec {
+      Some(spec) => StdSpec {
+        min_ready_seconds: spec.min_ready_seconds,
+        replicas: spec.replicas,
+        template: spec.template.clone(),
+      },
+      None => StdSpec {
+        min_ready_seconds: None,
+        replicas: None,
+        template: None,
+      },
+    };
+
+    StdResource { metadata, spec }
+  }
+}
+
+impl From<&apps::v1::StatefulSet> for StdResource {
+  fn from(sset: &apps::v1::StatefulSet) -> Self {
+    let metadata = get_metadata(&sset.metadata, Kind::StatefulSet);
+    let spec = match &sset.spec {
+      Some(spec) => StdSpec {
+        min_ready_seconds: spec.min_ready_seconds,
+        replicas: spec.replicas,
+        template: Some(spec.template.clone()),
+      },
+      None => StdSpec {
+        min_ready_seconds: None,
+        replicas: None,
+        template: None,
+      },
+    };
+
+    StdResource { metadata, spec }
+  }
+}
+
+impl From<&apps::v1::DaemonSet> for StdResource {
+  fn from(dset: &apps::v1::DaemonSet) -> Self {
+    let metadata = get_metadata(&dset.metadata, Kind::DaemonSet);
+    let spec = match &dset.spec {
+      Some(spec) => StdSpec {
+        min_ready_seconds: spec.min_ready_seconds,
+        replicas: None,
+        template: Some(spec.template.clone()),
+      },
+      None => StdSpec {
+        min_ready_seconds: None,
+        replicas: None,
+        template: None,
+      },
+    };
+
+    StdResource { metadata, spec }
+  }
+}
+
+impl From<&batch::v1::Job> for StdResource {
+  fn from(job: &batch::v1::Job) -> Self {
+    let metadata = get_metadata(&job.metadata, Kind::Job);
+    let spec = match &job.spec {
+      Some(spec) => StdSpec {
+        min_ready_seconds: None,
+        replicas: None,
+        template: Some(spec.template.clone()),
+      },
+      None => StdSpec {
+        min_ready_seconds: None,
+        replicas: None,
+        template: None,
+      },
+    };
+
+    StdResource { metadata, spec }
+  }
+}
+
+impl From<&batch::v1::CronJob> for StdResource {
+  fn from(cjob: &batch::v1::CronJob) -> Self {
+    let metadata = get_metadata(&cjob.metadata, Kind::CronJob);
+    let spec = match &cjob.spec {
+      Some(spec) => StdSpec {
+        min_ready_seconds: None,
+        replicas: None,
+        template: spec.job_template.spec.as_ref().map(|spec| spec.template.clone()),
+      },
+      None => StdSpec {
+        min_ready_seconds: None,
+        replicas: None,
+        template: None,
+      },
+    };
+
+    StdResource { metadata, spec }
+  }
+}
+
 async fn get_deployments(client: &Client) -> Result<Vec<StdResource>> {
   let api: Api<apps::v1::Deployment> = Api::all(client.to_owned());
   let deployment_list = api.list(&Default::default()).await?;
 
-  let deployments = deployment_list
-    .items
+  Ok(deployment_list.items.iter().map(StdResource::from).collect())
+}
+
+/// ReplicaSets managed by an inspected workload (i.e. - a Deployment or Rollout) are covered by their controller,
+/// and ReplicaSets scaled to zero (i.e. - the prior revisions of a Deployment) have no pods to be affected
+fn replicaset_resources(replicasets: &[apps::v1::ReplicaSet], owners: &Owners) -> Vec<StdResource> {
+  replicasets
     .iter()
-    .map(|dplmnt| {
-      let objmeta = dplmnt.metadata.clone();
+    .filter(|repl| repl.spec.as_ref().and_then(|spec| spec.replicas) != Some(0))
+    .filter_map(|repl| unless_managed(StdResource::from(repl), &repl.metadata, owners))
+    .collect()
+}
 
-      let metadata = StdMetadata {
-        name: objmeta.name.unwrap_or_default(),
-        namespace: objmeta.namespace.unwrap_or_default(),
-        kind: Kind::Deployment,
-        labels: objmeta.labels.unwrap_or_default(),
-        annotations: objmeta.annotations.unwrap_or_default(),
-      };
-      let spec = match &dplmnt.spec {
-        Some(spec) => StdSpec {
-          min_ready_seconds: spec.min_ready_seconds,
-          replicas: spec.replicas,
-          template: Some(spec.template.clone()),
-        },
-        None => StdSpec {
-          min_ready_seconds: None,
-          replicas: None,
-          template: None,
-        },
-      };
+async fn get_replicasets(client: &Client, owners: &Owners) -> Result<Vec<StdResource>> {
+  let api: Api<apps::v1::ReplicaSet> = Api::all(client.to_owned());
+  let replicaset_list = api.list(&Default::default()).await?;
 
-      StdResource { metadata, spec }
-    })
-    .collect();
+  Ok(replicaset_resources(&replicaset_list.items, owners))
+}
+
+async fn get_replicationcontrollers(client: &Client) -> Result<Vec<StdResource>> {
+  let api: Api<core::v1::ReplicationController> = Api::all(client.to_owned());
+  let rc_list = api.list(&Default::default()).await?;
 
-  Ok(deployments)
+  Ok(rc_list.items.iter().map(StdResource::from).collect())
 }
 
-async fn get_replicasets(client: &Client) -> Result<Vec<StdResource>> {
-  let api: Api<apps::v1::ReplicaSet> = Api::all(client.to_owned());
-  let replicaset_list = api.list(&Default::default()).await?;
+/// Custom resource workload types, and their plural resource name, inspected through the dynamic API
+///
+/// These follow the shape of the built-in workload types (`spec.replicas`, `spec.minReadySeconds`, and
+/// `spec.template`); support for another workload type is added with its `Kind` and an entry here
+const CUSTOM_WORKLOADS: [(Kind, &str); 2] = [(Kind::Rollout, "rollouts"), (Kind::CloneSet, "clonesets")];
+
+/// The subset of the spec that custom workload types share with the built-in workload types
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CustomWorkloadSpec {
+  min_ready_seconds: Option<i32>,
+  replicas: Option<i32>,
+  template: Option<PodTemplateSpec>,
+}
 
-  let replicasets = replicaset_list
-    .items
-    .iter()
-    .filter_map(|repl| match repl.metadata.owner_references {
-      None => {
-        let objmeta = repl.metadata.clone();
-
-        let metadata = StdMetadata {
-          name: objmeta.name.unwrap_or_default(),
-          namespace: objmeta.namespace.unwrap_or_default(),
-          kind: Kind::ReplicaSet,
-          labels: objmeta.labels.unwrap_or_default(),
-          annotations: objmeta.annotations.unwrap_or_default(),
-        };
-        let spec = match &repl.spec {
-          Some(spec) => StdSpec {
-            min_ready_seconds: spec.min_ready_seconds,
-            replicas: spec.replicas,
-            template: spec.template.clone(),
-          },
-          None => StdSpec {
-            min_ready_seconds: None,
-            replicas: None,
-            template: None,
-          },
-        };
+impl StdResource {
+  fn from_custom_workload(metadata: &ObjectMeta, spec: CustomWorkloadSpec, kind: Kind) -> Self {
+    StdResource {
+      metadata: get_metadata(metadata, kind),
+      spec: StdSpec {
+        min_ready_seconds: spec.min_ready_seconds,
+        replicas: spec.replicas,
+        template: spec.template,
+      },
+    }
+  }
+}
 
-        Some(StdResource { metadata, spec })
+/// Returns the custom workloads of the types in `CUSTOM_WORKLOADS` that are served by the cluster
+async fn get_custom_workloads(client: &Client) -> Result<Vec<StdResource>> {
+  let mut resources = Vec::new();
+
+  for (kind, plural) in CUSTOM_WORKLOADS {
+    let (group, version) = kind.api_version().split_once('/').unwrap_or(("", kind.api_version()));
+    let gvk = GroupVersionKind::gvk(group, version, &kind.to_string());
+    let api: Api<DynamicObject> = Api::all_with(client.to_owned(), &ApiResource::from_gvk_with_plural(&gvk, plural));
+
+    let objects = match api.list(&Default::default()).await {
+      Ok(list) => list.items,
+      // The custom resource definition is not installed on the cluster
+      Err(kube::Error::Api(err)) if err.code == 404 => continue,
+      Err(err) => {
+        warn!("Unable to list {}: {err}", kind);
+        continue;
       }
-      Some(_) => None,
-    })
-    .collect();
+    };
 
-  Ok(replicasets)
+    for object in objects {
+      let name = object.metadata.name.to_owned().unwrap_or_default();
+      // A workload that cannot be read is skipped rather than halting the analysis of the cluster
+      match serde_json::from_value::<CustomWorkloadSpec>(object.data["spec"].to_owned()) {
+        Ok(spec) => resources.push(StdResource::from_custom_workload(
+          &object.metadata,
+          spec,
+          kind.to_owned(),
+        )),
+        Err(err) => warn!("Skipping {kind} {name}: {err}"),
+      }
+    }
+  }
+
+  Ok(resources)
 }
 
 async fn get_statefulsets(client: &Client) -> Result<Vec<StdResource>> {
   let api: Api<apps::v1::StatefulSet> = Api::all(client.to_owned());
   let statefulset_list = api.list(&Default::default()).await?;
 
-  let statefulsets = statefulset_list
-    .items
-    .iter()
-    .map(|sset| {
-      let objmeta = sset.metadata.clone();
-
-      let metadata = StdMetadata {
-        name: objmeta.name.unwrap_or_default(),
-        namespace: objmeta.namespace.unwrap_or_default(),
-        kind: Kind::StatefulSet,
-        labels: objmeta.labels.unwrap_or_default(),
-        annotations: objmeta.annotations.unwrap_or_default(),
-      };
-      let spec = match &sset.spec {
-        Some(spec) => StdSpec {
-          min_ready_seconds: spec.min_ready_seconds,
-          replicas: spec.replicas,
-          template: Some(spec.template.clone()),
-        },
-        None => StdSpec {
-          min_ready_seconds: None,
-          replicas: None,
-          template: None,
-        },
-      };
-
-      StdResource { metadata, spec }
-    })
-    .collect();
-
-  Ok(statefulsets)
+  Ok(statefulset_list.items.iter().map(StdResource::from).collect())
 }
 
 async fn get_daemonsets(client: &Client) -> Result<Vec<StdResource>> {
   let api: Api<apps::v1::DaemonSet> = Api::all(client.to_owned());
   let daemonset_list = api.list(&Default::default()).await?;
 
-  let daemonsets = daemonset_list
-    .items
+  Ok(daemonset_list.items.iter().map(StdResource::from).collect())
+}
+
+/// Jobs managed by an inspected workload (i.e. - a CronJob) are covered by their controller
+fn job_resources(jobs: &[batch::v1::Job], owners: &Owners) -> Vec<StdResource> {
+  jobs
     .iter()
-    .map(|dset| {
-      let objmeta = dset.metadata.clone();
+    .filter_map(|job| unless_managed(StdResource::from(job), &job.metadata, owners))
+    .collect()
+}
 
-      let metadata = StdMetadata {
-        name: objmeta.name.unwrap_or_default(),
-        namespace: objmeta.namespace.unwrap_or_default(),
-        kind: Kind::DaemonSet,
-        labels: objmeta.labels.unwrap_or_default(),
-        annotations: objmeta.annotations.unwrap_or_default(),
-      };
-      let spec = match &dset.spec {
-        Some(spec) => StdSpec {
-          min_ready_seconds: spec.min_ready_seconds,
-          replicas: None,
-          template: Some(spec.template.clone()),
-        },
-        None => StdSpec {
-          min_ready_seconds: None,
-          replicas: None,
-          template: None,
-        },
+async fn get_jobs(client: &Client, owners: &Owners) -> Result<Vec<StdResource>> {
+  let api: Api<batch::v1::Job> = Api::all(client.to_owned());
+  let job_list = api.list(&Default::default()).await?;
+
+  Ok(job_resources(&job_list.items, owners))
+}
+
+async fn get_cronjobs(client: &Client) -> Result<Vec<StdResource>> {
+  let api: Api<batch::v1::CronJob> = Api::all(client.to_owned());
+  let cronjob_list = api.list(&Default::default()).await?;
+
+  Ok(cronjob_list.items.iter().map(StdResource::from).collect())
+}
+
+/// A Kubernetes manifest read from a file (or stdin), outside of the cluster
+#[derive(Debug)]
+pub struct Manifest {
+  /// Path of the file the manifest was read from, or `-` for stdin
+  pub path: String,
+  pub value: serde_yaml::Value,
+}
+
+impl Manifest {
+  /// Parse the (multi-document) YAML or JSON contents into the manifests it contains
+  ///
+  /// Empty documents are skipped, and the items of a `List` are returned as individual manifests
+  pub fn parse(path: &str, contents: &str) -> Result<Vec<Manifest>> {
+    let mut manifests = Vec::new();
+
+    for document in serde_yaml::Deserializer::from_str(contents) {
+      let value = serde_yaml::Value::deserialize(document).with_context(|| format!("Unable to parse {path}"))?;
+      if !value.is_mapping() {
+        continue;
+      }
+
+      let items = match (value["kind"].as_str(), value["items"].as_sequence()) {
+        (Some("List"), Some(items)) => items.to_owned(),
+        _ => vec![value],
       };
 
-      StdResource { metadata, spec }
-    })
-    .collect();
+      manifests.extend(items.into_iter().map(|value| Manifest {
+        path: path.to_owned(),
+        value,
+      }));
+    }
+
+    Ok(manifests)
+  }
+
+  pub(crate) fn api_version(&self) -> &str {
+    self.value["apiVersion"].as_str().unwrap_or_default()
+  }
+
+  pub(crate) fn kind(&self) -> &str {
+    self.value["kind"].as_str().unwrap_or_default()
+  }
 
-  Ok(daemonsets)
+  pub(crate) fn name(&self) -> &str {
+    self.value["metadata"]["name"].as_str().unwrap_or_default()
+  }
+
+  pub(crate) fn namespace(&self) -> &str {
+    self.value["metadata"]["namespace"].as_str().unwrap_or_default()
+  }
+
+  /// Deserialize the manifest into the resource type, using the current API version of the resource
+  ///
+  /// Manifests written against a removed API version (i.e. - `extensions/v1beta1` Deployments) are
+  /// inspected as though they were written against the current API version, where compatible
+  fn to_resource<T: DeserializeOwned>(&self, kind: &Kind) -> Result<T> {
+    let mut value = self.value.clone();
+    value["apiVersion"] = serde_yaml::Value::from(kind.api_version());
+
+    Ok(serde_yaml::from_value(value)?)
+  }
 }
 
-async fn get_jobs(client: &Client) -> Result<Vec<StdResource>> {
-  let api: Api<batch::v1::Job> = Api::all(client.to_owned());
-  let job_list = api.list(&Default::default()).await?;
+/// Returns the workload resource described by the manifest, if it is a workload inspected by the checks
+pub(crate) fn get_manifest_resource(manifest: &Manifest) -> Result<Option<StdResource>> {
+  let resource = match manifest.kind() {
+    "CronJob" => StdResource::from(&manifest.to_resource::<batch::v1::CronJob>(&Kind::CronJob)?),
+    "DaemonSet" => StdResource::from(&manifest.to_resource::<apps::v1::DaemonSet>(&Kind::DaemonSet)?),
+    "Deployment" => StdResource::from(&manifest.to_resource::<apps::v1::Deployment>(&Kind::Deployment)?),
+    "Job" => StdResource::from(&manifest.to_resource::<batch::v1::Job>(&Kind::Job)?),
+    "ReplicaSet" => StdResource::from(&manifest.to_resource::<apps::v1::ReplicaSet>(&Kind::ReplicaSet)?),
+    "ReplicationController" => {
+      StdResource::from(&manifest.to_resource::<core::v1::ReplicationController>(&Kind::ReplicationController)?)
+    }
+    "StatefulSet" => StdResource::from(&manifest.to_resource::<apps::v1::StatefulSet>(&Kind::StatefulSet)?),
+    kind => match CUSTOM_WORKLOADS.iter().find(|(custom, _)| custom.to_string() == kind) {
+      Some((kind, _)) => StdResource::from_custom_workload(
+        &serde_yaml::from_value(manifest.value["metadata"].to_owned())?,
+        serde_yaml::from_value(manifest.value["spec"].to_owned())?,
+        kind.to_owned(),
+      ),
+      None => return Ok(None),
+    },
+  };
 
-  let jobs = job_list
-    .items
-    .iter()
-    .filter_map(|job| match job.metadata.owner_references {
-      None => {
-        let objmeta = job.metadata.clone();
-
-        let metadata = StdMetadata {
-          name: objmeta.name.unwrap_or_default(),
-          namespace: objmeta.namespace.unwrap_or_default(),
-          kind: Kind::Job,
-          labels: objmeta.labels.unwrap_or_default(),
-          annotations: objmeta.annotations.unwrap_or_default(),
-        };
-        let spec = match &job.spec {
-          Some(spec) => StdSpec {
-            min_ready_seconds: None,
-            replicas: None,
-            template: Some(spec.template.clone()),
-          },
-          None => StdSpec {
-            min_ready_seconds: None,
-            replicas: None,
-            template: None,
-          },
-        };
+  Ok(Some(resource))
+}
 
-        Some(StdResource { metadata, spec })
-      }
-      Some(_) => None,
-    })
-    .collect();
+pub(crate) async fn get_pod_disruption_budgets(client: &Client) -> Result<Vec<policy::v1::PodDisruptionBudget>> {
+  let api: Api<policy::v1::PodDisruptionBudget> = Api::all(client.to_owned());
+  let pdb_list = api.list(&Default::default()).await?;
 
-  Ok(jobs)
+  Ok(pdb_list.items)
 }
 
-async fn get_cronjobs(client: &Client) -> Result<Vec<StdResource>> {
-  let api: Api<batch::v1::CronJob> = Api::all(client.to_owned());
-  let cronjob_list = api.list(&Default::default()).await?;
+/// Returns all of the pods in the cluster
+pub(crate) async fn get_pods(client: &Client) -> Result<Vec<core::v1::Pod>> {
+  let api: Api<core::v1::Pod> = Api::all(client.to_owned());
+  let pod_list = api.list(&Default::default()).await?;
 
-  let cronjobs = cronjob_list
-    .items
+  Ok(pod_list.items)
+}
+
+/// Returns all of the APIServices registered with the API server, both local and aggregated
+pub(crate) async fn get_api_services(client: &Client) -> Result<Vec<APIService>> {
+  let api: Api<APIService> = Api::all(client.to_owned());
+  let api_service_list = api.list(&Default::default()).await?;
+
+  Ok(api_service_list.items)
+}
+
+/// Controllers of the intermediate resources (ReplicaSets and Jobs), keyed by namespace, kind, and name
+pub(crate) type Owners = BTreeMap<(String, String, String), OwnerReference>;
+
+/// Returns the controller of the object, if it has one
+pub(crate) fn get_controller(metadata: &ObjectMeta) -> Option<&OwnerReference> {
+  metadata
+    .owner_references
+    .as_ref()?
     .iter()
-    .map(|cjob| {
-      let objmeta = cjob.metadata.clone();
+    .find(|owner| owner.controller.unwrap_or(false))
+}
 
-      let metadata = StdMetadata {
-        name: objmeta.name.unwrap_or_default(),
-        namespace: objmeta.namespace.unwrap_or_default(),
-        kind: Kind::CronJob,
-        labels: objmeta.labels.unwrap_or_default(),
-        annotations: objmeta.annotations.unwrap_or_default(),
-      };
-      let spec = match &cjob.spec {
-        Some(spec) => StdSpec {
-          min_ready_seconds: None,
-          replicas: None,
-          template: spec.job_template.spec.as_ref().map(|spec| spec.template.clone()),
-        },
-        None => StdSpec {
-          min_ready_seconds: None,
-          replicas: None,
-          template: None,
-        },
-      };
+/// Returns the controllers of the ReplicaSets and Jobs in the cluster, used to resolve the top-level
+/// controller of a pod (i.e. - Pod -> ReplicaSet -> Deployment, or Pod -> Job -> CronJob)
+pub(crate) async fn get_owners(client: &Client) -> Result<Owners> {
+  let replicasets = Api::<apps::v1::ReplicaSet>::all(client.to_owned())
+    .list_metadata(&Default::default())
+    .await?
+    .items
+    .into_iter()
+    .map(|rs| ("ReplicaSet", rs.metadata));
+  let jobs = Api::<batch::v1::Job>::all(client.to_owned())
+    .list_metadata(&Default::default())
+    .await?
+    .items
+    .into_iter()
+    .map(|job| ("Job", job.metadata));
 
-      StdResource { metadata, spec }
-    })
-    .collect();
+  Ok(
+    replicasets
+      .chain(jobs)
+      .filter_map(|(kind, metadata)| {
+        let owner = get_controller(&metadata)?.to_owned();
+        let key = (
+          metadata.namespace.unwrap_or_default(),
+          kind.to_string(),
+          metadata.name.unwrap_or_default(),
+        );
+        Some((key, owner))
+      })
+      .collect(),
+  )
+}
+
+/// Returns the top-level controller of the object by following the controller owner references
+///
+/// Owner references that form a cycle are followed until an owner is revisited, which is then treated as the top-level controller
+pub(crate) fn get_top_level_controller(metadata: &ObjectMeta, owners: &Owners) -> Option<OwnerReference> {
+  let namespace = metadata.namespace.to_owned().unwrap_or_default();
+  let mut controller = get_controller(metadata)?.to_owned();
+  let mut visited = BTreeSet::new();
+
+  while visited.insert((controller.kind.to_owned(), controller.name.to_owned())) {
+    match owners.get(&(
+      namespace.to_owned(),
+      controller.kind.to_owned(),
+      controller.name.to_owned(),
+    )) {
+      Some(owner) => controller = owner.to_owned(),
+      None => break,
+    }
+  }
 
-  Ok(cronjobs)
+  Some(controller)
 }
 
-// // https://github.com/kube-rs/kube/issues/428
-// // https://github.com/kubernetes/apimachinery/blob/373a5f752d44989b9829888460844849878e1b6e/pkg/apis/meta/v1/helpers.go#L34
-// pub(crate) async fn get_pod_disruption_budgets(client: &Client) -> Result<Vec<PodDisruptionBudget>> {
-//   let api: Api<policy::v1beta1::PodDisruptionBudget> = Api::all(client.to_owned());
-//   let pdb_list = api.list(&Default::default()).await?;
+/// Returns true if the label selector matches the labels provided
+///
+/// An empty selector matches all labels, whereas the absence of a selector (handled by the caller) matches nothing
+/// <https://github.com/kubernetes/apimachinery/blob/373a5f752d44989b9829888460844849878e1b6e/pkg/apis/meta/v1/helpers.go#L34>
+pub(crate) fn selector_matches(selector: &LabelSelector, labels: &BTreeMap<String, String>) -> bool {
+  let match_labels = selector
+    .match_labels
+    .iter()
+    .flatten()
+    .all(|(key, value)| labels.get(key) == Some(value));
+
+  let match_expressions = selector.match_expressions.iter().flatten().all(|expression| {
+    let value = labels.get(&expression.key);
+    let values = expression.values.as_deref().unwrap_or_default();
+
+    match expression.operator.as_str() {
+      "In" => value.map(|value| values.contains(value)).unwrap_or(false),
+      "NotIn" => value.map(|value| !values.contains(value)).unwrap_or(true),
+      "Exists" => value.is_some(),
+      "DoesNotExist" => value.is_none(),
+      // Unknown operators are invalid and therefore do not match
+      _ => false,
+    }
+  });
 
-//   Ok(pdb_list.items)
-// }
+  match_labels && match_expressions
+}
 
 pub(crate) async fn get_podsecuritypolicies(
   client: &Client,
-  target_version: &str,
+  target_version: &version::Version,
 ) -> Result<Vec<checks::PodSecurityPolicy>> {
   let api: Api<policy::v1beta1::PodSecurityPolicy> = Api::all(client.to_owned());
   let psp_list = api.list(&Default::default()).await?;
 
-  let target_version = version::parse_minor(target_version)?;
-  let remediation = if target_version >= 25 {
+  let remediation = if *target_version >= version::Version::new(1, 25) {
     finding::Remediation::Required
   } else {
     finding::Remediation::Recommended
@@ -365,6 +714,7 @@ pub(crate) async fn get_podsecuritypolicies(
         name: objmeta.name.unwrap_or_default(),
         namespace: objmeta.namespace.unwrap_or_default(),
         kind: Kind::PodSecurityPolicy,
+        owner: None,
       };
 
       let finding = finding::Finding {
@@ -388,6 +738,24 @@ pub struct Resource {
   pub namespace: String,
   /// Kind of the resource
   pub kind: Kind,
+  /// Controller of the resource (`<kind>/<name>`), if it is managed by another resource (i.e. - an operator)
+  #[tabled(display_with = "display_owner")]
+  pub owner: Option<String>,
+}
+
+fn display_owner(owner: &Option<String>) -> String {
+  owner.to_owned().unwrap_or_else(|| "-".to_string())
+}
+
+impl Resource {
+  /// Creates the common finding entry for a finding reported on this resource
+  pub(crate) fn to_entry(&self, finding: &finding::Finding) -> finding::Entry {
+    finding::Entry {
+      api_version: Some(self.kind.api_version().to_string()),
+      namespace: (!self.namespace.is_empty()).then(|| self.namespace.to_owned()),
+      ..finding::Entry::new(finding, &self.kind.to_string(), &self.name)
+    }
+  }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -397,6 +765,8 @@ pub struct StdMetadata {
   pub kind: Kind,
   pub labels: BTreeMap<String, String>,
   pub annotations: BTreeMap<String, String>,
+  /// Controller of the resource (`<kind>/<name>`), if it has one
+  pub owner: Option<String>,
 }
 
 /// This is a generalized spec used across all resource types that
@@ -419,12 +789,60 @@ pub struct StdResource {
   pub spec: StdSpec,
 }
 
+/// Topology keys of the availability zone of nodes, including the deprecated beta label
+pub(crate) const ZONE_TOPOLOGY_KEYS: [&str; 2] =
+  ["topology.kubernetes.io/zone", "failure-domain.beta.kubernetes.io/zone"];
+
+/// Number of nodes at which inter-pod (anti-)affinity starts to slow down scheduling significantly
+/// <https://kubernetes.io/docs/concepts/scheduling-eviction/assign-pod-node/#inter-pod-affinity-and-anti-affinity>
+const LARGE_CLUSTER_NODE_COUNT: usize = 100;
+
+/// Minimum `failureThreshold` of a livenessProbe, the Kubernetes default; lower values restart containers
+/// on the first transient failure, such as those caused by control plane or node churn
+const MIN_LIVENESS_FAILURE_THRESHOLD: i32 = 3;
+
+/// K8S006 - returns the issues found with the probes of a container
+///
+/// A livenessProbe identical to the readinessProbe restarts containers that are only temporarily unready (i.e. -
+/// overloaded while pods are rescheduled during node replacement), which cascades across the remaining replicas
+fn probe_issues(container: &core::v1::Container) -> Vec<(finding::Remediation, &'static str)> {
+  let mut issues = Vec::new();
+
+  if container.readiness_probe.is_none() {
+    issues.push((finding::Remediation::Required, "readinessProbe not set"));
+  }
+
+  if let Some(liveness) = &container.liveness_probe {
+    if container.readiness_probe.as_ref() == Some(liveness) {
+      issues.push((
+        finding::Remediation::Required,
+        "livenessProbe identical to readinessProbe",
+      ));
+    }
+    if container.startup_probe.is_none() && liveness.initial_delay_seconds.unwrap_or_default() <= 0 {
+      issues.push((
+        finding::Remediation::Recommended,
+        "livenessProbe without startupProbe or initialDelaySeconds",
+      ));
+    }
+    if liveness.failure_threshold.unwrap_or(MIN_LIVENESS_FAILURE_THRESHOLD) < MIN_LIVENESS_FAILURE_THRESHOLD {
+      issues.push((
+        finding::Remediation::Recommended,
+        "livenessProbe failureThreshold below 3",
+      ));
+    }
+  }
+
+  issues
+}
+
 impl checks::K8sFindings for StdResource {
   fn get_resource(&self) -> Resource {
     Resource {
       name: self.metadata.name.to_owned(),
       namespace: self.metadata.namespace.to_owned(),
       kind: self.metadata.kind.to_owned(),
+      owner: self.metadata.owner.to_owned(),
     }
   }
 
@@ -456,7 +874,7 @@ impl checks::K8sFindings for StdResource {
   fn min_ready_seconds(&self) -> Option<checks::MinReadySeconds> {
     let resource = self.get_resource();
 
-    if vec![Kind::CronJob, Kind::DaemonSet, Kind::Job].contains(&resource.kind) {
+    if [Kind::CronJob, Kind::DaemonSet, Kind::Job].contains(&resource.kind) {
       return None;
     }
 
@@ -496,75 +914,189 @@ impl checks::K8sFindings for StdResource {
     }
   }
 
-  fn readiness_probe(&self) -> Option<checks::Probe> {
-    let pod_template = self.spec.template.to_owned();
-
+  fn probes(&self) -> Vec<checks::Probe> {
     let resource = self.get_resource();
     match resource.kind {
-      Kind::DaemonSet | Kind::Job | Kind::CronJob => return None,
+      Kind::DaemonSet | Kind::Job | Kind::CronJob => return vec![],
       _ => (),
     }
 
-    match pod_template {
-      Some(pod_template) => {
-        let containers = pod_template.spec.unwrap_or_default().containers;
+    let containers = self
+      .spec
+      .template
+      .as_ref()
+      .and_then(|template| template.spec.as_ref())
+      .map(|spec| spec.containers.as_slice())
+      .unwrap_or_default();
 
-        for container in containers {
-          if container.readiness_probe.is_none() {
-            let remediation = finding::Remediation::Required;
-            let finding = finding::Finding {
+    containers
+      .iter()
+      .flat_map(|container| {
+        probe_issues(container)
+          .into_iter()
+          .map(|(remediation, issue)| checks::Probe {
+            finding: finding::Finding {
               code: finding::Code::K8S006,
               symbol: remediation.symbol(),
               remediation,
-            };
-
-            // As soon as we find one container without a readiness probe, we return the finding
-            return Some(checks::Probe {
-              finding,
-              resource: self.get_resource(),
-              readiness_probe: container.readiness_probe.is_some(),
-            });
-          }
-        }
-        None
-      }
-      None => None,
-    }
+            },
+            resource: self.get_resource(),
+            container: container.name.to_owned(),
+            issue: issue.to_string(),
+          })
+      })
+      .collect()
   }
 
-  fn pod_topology_distribution(&self) -> Option<checks::PodTopologyDistribution> {
-    let pod_template = self.spec.template.to_owned();
+  fn pod_disruption_budget(&self, pdbs: &[policy::v1::PodDisruptionBudget]) -> Option<checks::PodDisruptionBudget> {
+    let resource = self.get_resource();
+    match resource.kind {
+      Kind::Deployment
+      | Kind::ReplicaSet
+      | Kind::ReplicationController
+      | Kind::StatefulSet
+      | Kind::Rollout
+      | Kind::CloneSet => (),
+      _ => return None,
+    }
+
+    let labels = self
+      .spec
+      .template
+      .as_ref()
+      .and_then(|template| template.metadata.as_ref())
+      .and_then(|metadata| metadata.labels.to_owned())
+      .unwrap_or_default();
 
+    let covering: Vec<&policy::v1::PodDisruptionBudget> = pdbs
+      .iter()
+      .filter(|pdb| pdb.metadata.namespace.as_deref().unwrap_or_default() == resource.namespace)
+      .filter(|pdb| {
+        pdb
+          .spec
+          .as_ref()
+          .and_then(|spec| spec.selector.as_ref())
+          .map(|selector| selector_matches(selector, &labels))
+          .unwrap_or(false)
+      })
+      .collect();
+
+    let available = covering.iter().any(|pdb| {
+      pdb
+        .spec
+        .as_ref()
+        .map(|spec| spec.min_available.is_some() || spec.max_unavailable.is_some())
+        .unwrap_or(false)
+    });
+    if available {
+      return None;
+    }
+
+    let remediation = finding::Remediation::Required;
+    let finding = finding::Finding {
+      code: finding::Code::K8S004,
+      symbol: remediation.symbol(),
+      remediation,
+    };
+
+    Some(checks::PodDisruptionBudget {
+      finding,
+      resource,
+      pdbs: covering
+        .iter()
+        .map(|pdb| pdb.metadata.name.to_owned().unwrap_or_default())
+        .collect(),
+    })
+  }
+
+  fn pod_topology_distribution(&self, node_count: Option<usize>) -> Option<checks::PodTopologyDistribution> {
     let resource = self.get_resource();
     match resource.kind {
       Kind::DaemonSet | Kind::Job | Kind::CronJob => return None,
       _ => (),
     }
 
-    match pod_template {
-      Some(pod_template) => {
-        let pod_spec = pod_template.spec.unwrap_or_default();
-        if pod_spec.affinity.is_none() && pod_spec.topology_spread_constraints.is_none() {
-          let remediation = finding::Remediation::Required;
-          let finding = finding::Finding {
-            code: finding::Code::K8S005,
-            symbol: remediation.symbol(),
-            remediation,
-          };
+    let pod_spec = self.spec.template.as_ref()?.spec.to_owned().unwrap_or_default();
+    let replicas = self.spec.replicas.unwrap_or(1);
+
+    let anti_affinity = pod_spec
+      .affinity
+      .as_ref()
+      .and_then(|affinity| affinity.pod_anti_affinity.to_owned())
+      .unwrap_or_default();
+    let required_terms = anti_affinity
+      .required_during_scheduling_ignored_during_execution
+      .unwrap_or_default();
+    let anti_affinity_keys: Vec<String> = required_terms
+      .iter()
+      .map(|term| term.topology_key.to_owned())
+      .chain(
+        anti_affinity
+          .preferred_during_scheduling_ignored_during_execution
+          .unwrap_or_default()
+          .into_iter()
+          .map(|term| term.pod_affinity_term.topology_key),
+      )
+      .collect();
+    let constraints = pod_spec.topology_spread_constraints.unwrap_or_default();
+    let zone_constraints: Vec<&core::v1::TopologySpreadConstraint> = constraints
+      .iter()
+      .filter(|constraint| ZONE_TOPOLOGY_KEYS.contains(&constraint.topology_key.as_str()))
+      .collect();
+
+    let (remediation, issue) = if anti_affinity_keys.is_empty() && constraints.is_empty() {
+      (
+        finding::Remediation::Required,
+        "No podAntiAffinity or topologySpreadConstraints".to_string(),
+      )
+    } else if zone_constraints.is_empty()
+      && !anti_affinity_keys
+        .iter()
+        .any(|key| ZONE_TOPOLOGY_KEYS.contains(&key.as_str()))
+    {
+      (finding::Remediation::Required, "Not spread across zones".to_string())
+    } else if let Some(constraint) = zone_constraints
+      .iter()
+      .find(|constraint| replicas > 1 && constraint.max_skew >= replicas)
+    {
+      (
+        finding::Remediation::Recommended,
+        format!(
+          "Zone maxSkew of {} does not spread {replicas} replicas",
+          constraint.max_skew
+        ),
+      )
+    } else if !zone_constraints.is_empty()
+      && zone_constraints
+        .iter()
+        .all(|constraint| constraint.when_unsatisfiable == "ScheduleAnyway")
+    {
+      (
+        finding::Remediation::Recommended,
+        "Zone spread is not enforced (ScheduleAnyway)".to_string(),
+      )
+    } else if !required_terms.is_empty() && node_count.unwrap_or_default() >= LARGE_CLUSTER_NODE_COUNT {
+      (
+        finding::Remediation::Recommended,
+        "Prefer topologySpreadConstraints over required podAntiAffinity".to_string(),
+      )
+    } else {
+      return None;
+    };
 
-          // As soon as we find one container without a readiness probe, we return the finding
-          Some(checks::PodTopologyDistribution {
-            finding,
-            resource: self.get_resource(),
-            anti_affinity: pod_spec.affinity.is_some(),
-            topology_spread_constraints: pod_spec.topology_spread_constraints.is_some(),
-          })
-        } else {
-          None
-        }
-      }
-      None => None,
-    }
+    let finding = finding::Finding {
+      code: finding::Code::K8S005,
+      symbol: remediation.symbol(),
+      remediation,
+    };
+
+    Some(checks::PodTopologyDistribution {
+      finding,
+      resource,
+      anti_affinity: !anti_affinity_keys.is_empty(),
+      topology_spread_constraints: !constraints.is_empty(),
+      issue,
+    })
   }
 
   fn termination_grace_period(&self) -> Option<checks::TerminationGracePeriod> {
@@ -609,11 +1141,10 @@ impl checks::K8sFindings for StdResource {
     }
   }
 
-  fn docker_socket(&self, target_version: &str) -> Option<checks::DockerSocket> {
+  fn docker_socket(&self, target_version: &version::Version) -> Option<checks::DockerSocket> {
     let pod_template = self.spec.template.to_owned();
 
-    let target_version = version::parse_minor(target_version).unwrap();
-    let remediation = if target_version >= 24 {
+    let remediation = if *target_version >= version::Version::new(1, 24) {
       finding::Remediation::Required
     } else {
       finding::Remediation::Recommended
@@ -649,12 +1180,15 @@ impl checks::K8sFindings for StdResource {
 }
 
 pub async fn get_resources(client: &Client) -> Result<Vec<StdResource>> {
+  let owners = get_owners(client).await?;
   let cronjobs = get_cronjobs(client).await?;
   let daemonsets = get_daemonsets(client).await?;
   let deployments = get_deployments(client).await?;
-  let jobs = get_jobs(client).await?;
-  let replicasets = get_replicasets(client).await?;
+  let jobs = get_jobs(client, &owners).await?;
+  let replicasets = get_replicasets(client, &owners).await?;
+  let replicationcontrollers = get_replicationcontrollers(client).await?;
   let statefulsets = get_statefulsets(client).await?;
+  let custom_workloads = get_custom_workloads(client).await?;
 
   let mut resources = Vec::new();
   resources.extend(cronjobs);
@@ -662,7 +1196,332 @@ pub async fn get_resources(client: &Client) -> Result<Vec<StdResource>> {
   resources.extend(deployments);
   resources.extend(jobs);
   resources.extend(replicasets);
+  resources.extend(replicationcontrollers);
   resources.extend(statefulsets);
+  resources.extend(custom_workloads);
 
   Ok(resources)
 }
+
+#[cfg(test)]
+mod tests {
+  use checks::K8sFindings;
+
+  use super::*;
+
+  fn from_yaml<T: DeserializeOwned>(yaml: &str) -> T {
+    serde_yaml::from_str(yaml).unwrap()
+  }
+
+  #[test]
+  fn can_match_selector() {
+    let labels = BTreeMap::from([
+      ("app".to_string(), "web".to_string()),
+      ("tier".to_string(), "frontend".to_string()),
+    ]);
+
+    let selector_matches_yaml = |yaml: &str| selector_matches(&from_yaml(yaml), &labels);
+
+    assert!(selector_matches_yaml("{}"));
+    assert!(selector_matches_yaml("matchLabels: {app: web}"));
+    assert!(!selector_matches_yaml("matchLabels: {app: web, tier: backend}"));
+    assert!(selector_matches_yaml(
+      "matchExpressions: [{key: tier, operator: In, values: [frontend, backend]}]"
+    ));
+    assert!(!selector_matches_yaml(
+      "matchExpressions: [{key: tier, operator: NotIn, values: [frontend]}]"
+    ));
+    assert!(selector_matches_yaml(
+      "matchExpressions: [{key: app, operator: Exists}, {key: env, operator: DoesNotExist}]"
+    ));
+    assert!(!selector_matches_yaml(
+      "matchExpressions: [{key: env, operator: Exists}]"
+    ));
+  }
+
+  #[test]
+  fn can_get_manifest_workloads() {
+    let contents = r#"
+apiVersion: v1
+kind: ReplicationController
+metadata: {name: legacy, namespace: default}
+spec:
+  replicas: 1
+  template: {spec: {containers: [{name: legacy, image: legacy}]}}
+---
+apiVersion: argoproj.io/v1alpha1
+kind: Rollout
+metadata: {name: canary, namespace: default}
+spec:
+  replicas: 5
+  minReadySeconds: 10
+  strategy: {canary: {steps: [{setWeight: 20}]}}
+  template: {spec: {containers: [{name: canary, image: canary}]}}
+---
+apiVersion: v1
+kind: ConfigMap
+metadata: {name: config, namespace: default}
+"#;
+    let manifests = Manifest::parse("-", contents).unwrap();
+    let resources: Vec<StdResource> = manifests
+      .iter()
+      .filter_map(|manifest| get_manifest_resource(manifest).unwrap())
+      .collect();
+
+    assert_eq!(resources.len(), 2);
+    assert_eq!(resources[0].metadata.kind, Kind::ReplicationController);
+    assert!(resources[0].min_replicas().is_some());
+
+    let rollout = &resources[1];
+    assert_eq!(rollout.metadata.kind, Kind::Rollout);
+    assert_eq!(rollout.metadata.kind.api_version(), "argoproj.io/v1alpha1");
+    assert!(rollout.min_replicas().is_none());
+    assert!(rollout.min_ready_seconds().is_none());
+    assert_eq!(rollout.probes()[0].container, "canary");
+  }
+
+  #[test]
+  fn can_resolve_owner() {
+    let replicaset = |name: &str, owner_kind: &str, replicas: i32| -> apps::v1::ReplicaSet {
+      from_yaml(&format!(
+        "metadata: {{name: {name}, namespace: default, ownerReferences: [{{apiVersion: v1, kind: {owner_kind}, name: web, uid: '1', controller: true}}]}}\nspec: {{replicas: {replicas}, selector: {{}}}}"
+      ))
+    };
+    let names = |resources: Vec<StdResource>| -> Vec<(String, Option<String>)> {
+      resources
+        .into_iter()
+        .map(|resource| (resource.metadata.name, resource.metadata.owner))
+        .collect()
+    };
+
+    let replicasets = vec![
+      replicaset("deployment", "Deployment", 3),
+      replicaset("rollout", "Rollout", 3),
+      // Managed by a controller that is not inspected, the findings are reported on the ReplicaSet along with its owner
+      replicaset("operator", "Operator", 3),
+      // Scaled to zero, there are no pods to be affected
+      replicaset("previous", "Operator", 0),
+      from_yaml("metadata: {name: unowned, namespace: default}"),
+    ];
+    assert_eq!(
+      names(replicaset_resources(&replicasets, &Owners::new())),
+      vec![
+        ("operator".to_string(), Some("Operator/web".to_string())),
+        ("unowned".to_string(), None),
+      ]
+    );
+
+    let job = |name: &str, owner_kind: &str, owner_name: &str| -> batch::v1::Job {
+      from_yaml(&format!(
+        "metadata: {{name: {name}, namespace: default, ownerReferences: [{{apiVersion: v1, kind: {owner_kind}, name: {owner_name}, uid: '1', controller: true}}]}}"
+      ))
+    };
+    let owner: OwnerReference =
+      from_yaml("{apiVersion: v1, kind: Workflow, name: nightly, uid: '2', controller: true}");
+    let jobs = vec![
+      job("backup", "CronJob", "backup"),
+      job("import", "Workflow", "import"),
+      job("nested", "Job", "parent"),
+    ];
+    // The owner of the intermediate Job is resolved to its top-level controller
+    let owners = Owners::from([(("default".to_string(), "Job".to_string(), "parent".to_string()), owner)]);
+    assert_eq!(
+      names(job_resources(&jobs, &owners)),
+      vec![
+        ("import".to_string(), Some("Workflow/import".to_string())),
+        ("nested".to_string(), Some("Workflow/nightly".to_string())),
+      ]
+    );
+  }
+
+  #[test]
+  fn can_resolve_top_level_controller() {
+    let owner = |kind: &str, name: &str| -> OwnerReference {
+      from_yaml(&format!(
+        "{{apiVersion: v1, kind: {kind}, name: {name}, uid: '1', controller: true}}"
+      ))
+    };
+    let pod: core::v1::Pod = from_yaml(
+      "metadata: {name: web-5d4f8-x2v9q, namespace: default, ownerReferences: [{apiVersion: apps/v1, kind: ReplicaSet, name: web-5d4f8, uid: '1', controller: true}]}",
+    );
+    let key = |kind: &str, name: &str| ("default".to_string(), kind.to_string(), name.to_string());
+
+    let owners = Owners::from([(key("ReplicaSet", "web-5d4f8"), owner("Deployment", "web"))]);
+    let controller = get_top_level_controller(&pod.metadata, &owners).unwrap();
+    assert_eq!(
+      (controller.kind.as_str(), controller.name.as_str()),
+      ("Deployment", "web")
+    );
+
+    // Owner references that form a cycle terminate at the first owner revisited
+    let owners = Owners::from([
+      (key("ReplicaSet", "web-5d4f8"), owner("Job", "web")),
+      (key("Job", "web"), owner("ReplicaSet", "web-5d4f8")),
+    ]);
+    let controller = get_top_level_controller(&pod.metadata, &owners).unwrap();
+    assert_eq!(
+      (controller.kind.as_str(), controller.name.as_str()),
+      ("ReplicaSet", "web-5d4f8")
+    );
+
+    let owners = Owners::from([(key("ReplicaSet", "web-5d4f8"), owner("ReplicaSet", "web-5d4f8"))]);
+    let controller = get_top_level_controller(&pod.metadata, &owners).unwrap();
+    assert_eq!(
+      (controller.kind.as_str(), controller.name.as_str()),
+      ("ReplicaSet", "web-5d4f8")
+    );
+  }
+
+  #[test]
+  fn can_check_pod_disruption_budget() {
+    let deployment: apps::v1::Deployment = from_yaml(
+      r#"
+apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: web
+  namespace: default
+spec:
+  selector:
+    matchLabels:
+      app: web
+  template:
+    metadata:
+      labels:
+        app: web
+"#,
+    );
+    let resource = StdResource::from(&deployment);
+    let pdb = |namespace: &str, spec: &str| -> policy::v1::PodDisruptionBudget {
+      from_yaml(&format!(
+        "apiVersion: policy/v1\nkind: PodDisruptionBudget\nmetadata: {{name: web, namespace: {namespace}}}\nspec: {spec}"
+      ))
+    };
+
+    let finding = resource.pod_disruption_budget(&[]).unwrap();
+    assert!(finding.pdbs.is_empty());
+
+    // Selectors only apply within the namespace of the podDisruptionBudget
+    let other = pdb("other", "{selector: {matchLabels: {app: web}}, minAvailable: 1}");
+    assert!(resource.pod_disruption_budget(&[other]).is_some());
+
+    let unset = pdb("default", "{selector: {matchLabels: {app: web}}}");
+    let finding = resource.pod_disruption_budget(&[unset]).unwrap();
+    assert_eq!(finding.pdbs, vec!["web"]);
+
+    let covering = pdb("default", "{selector: {matchLabels: {app: web}}, maxUnavailable: 25%}");
+    assert!(resource.pod_disruption_budget(&[covering]).is_none());
+  }
+
+  #[test]
+  fn can_check_pod_topology_distribution() {
+    let check = |replicas: i32, spec: &str, node_count: Option<usize>| {
+      let deployment: apps::v1::Deployment = from_yaml(&format!(
+        "metadata: {{name: web, namespace: default}}\nspec: {{replicas: {replicas}, selector: {{}}, template: {{spec: {spec}}}}}"
+      ));
+      StdResource::from(&deployment)
+        .pod_topology_distribution(node_count)
+        .map(|f| (f.finding.remediation.to_string(), f.issue))
+    };
+    let spread = |key: &str, max_skew: i32, when: &str| {
+      format!(
+        "{{containers: [], topologySpreadConstraints: [{{topologyKey: {key}, maxSkew: {max_skew}, whenUnsatisfiable: {when}}}]}}"
+      )
+    };
+    let required_anti_affinity = |key: &str| {
+      format!(
+        "{{containers: [], affinity: {{podAntiAffinity: {{requiredDuringSchedulingIgnoredDuringExecution: [{{topologyKey: {key}}}]}}}}}}"
+      )
+    };
+
+    let (remediation, issue) = check(3, "{containers: []}", None).unwrap();
+    assert_eq!(remediation, "Required");
+    assert_eq!(issue, "No podAntiAffinity or topologySpreadConstraints");
+
+    // Spreading across nodes alone does not protect against the loss of a zone
+    let hostname = spread("kubernetes.io/hostname", 1, "DoNotSchedule");
+    assert_eq!(check(3, &hostname, None).unwrap().1, "Not spread across zones");
+    let (_, issue) = check(3, &required_anti_affinity("kubernetes.io/hostname"), None).unwrap();
+    assert_eq!(issue, "Not spread across zones");
+
+    let zone = "topology.kubernetes.io/zone";
+    assert!(check(3, &spread(zone, 1, "DoNotSchedule"), None).is_none());
+    let (remediation, issue) = check(3, &spread(zone, 3, "DoNotSchedule"), None).unwrap();
+    assert_eq!(remediation, "Recommended");
+    assert_eq!(issue, "Zone maxSkew of 3 does not spread 3 replicas");
+    let (_, issue) = check(3, &spread(zone, 1, "ScheduleAnyway"), None).unwrap();
+    assert_eq!(issue, "Zone spread is not enforced (ScheduleAnyway)");
+
+    assert!(check(3, &required_anti_affinity(zone), Some(10)).is_none());
+    let (_, issue) = check(3, &required_anti_affinity(zone), Some(300)).unwrap();
+    assert_eq!(issue, "Prefer topologySpreadConstraints over required podAntiAffinity");
+  }
+
+  #[test]
+  fn can_check_probes() {
+    let deployment: apps::v1::Deployment = from_yaml(
+      r#"
+apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: web
+  namespace: default
+spec:
+  selector:
+    matchLabels:
+      app: web
+  template:
+    spec:
+      containers:
+        - name: none
+          image: web
+        - name: identical
+          image: web
+          readinessProbe: {httpGet: {path: /healthz, port: 8080}}
+          livenessProbe: {httpGet: {path: /healthz, port: 8080}}
+          startupProbe: {httpGet: {path: /healthz, port: 8080}}
+        - name: eager
+          image: web
+          readinessProbe: {httpGet: {path: /ready, port: 8080}}
+          livenessProbe: {httpGet: {path: /healthz, port: 8080}, failureThreshold: 1}
+        - name: good
+          image: web
+          readinessProbe: {httpGet: {path: /ready, port: 8080}}
+          livenessProbe: {httpGet: {path: /healthz, port: 8080}, initialDelaySeconds: 15}
+"#,
+    );
+
+    let findings = StdResource::from(&deployment).probes();
+    let issues: Vec<(&str, &str, String)> = findings
+      .iter()
+      .map(|f| {
+        (
+          f.container.as_str(),
+          f.issue.as_str(),
+          f.finding.remediation.to_string(),
+        )
+      })
+      .collect();
+    assert_eq!(
+      issues,
+      vec![
+        ("none", "readinessProbe not set", "Required".to_string()),
+        (
+          "identical",
+          "livenessProbe identical to readinessProbe",
+          "Required".to_string()
+        ),
+        (
+          "eager",
+          "livenessProbe without startupProbe or initialDelaySeconds",
+          "Recommended".to_string()
+        ),
+        (
+          "eager",
+          "livenessProbe failureThreshold below 3",
+          "Recommended".to_string()
+        ),
+      ]
+    );
+  }
+}
@@ -0,0 +1,694 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::Result;
+use aws_sdk_eks::types::{Addon, AddonStatus};
+use k8s_openapi::{
+  api::{apps, core, storage},
+  apimachinery::pkg::apis::meta::v1::ObjectMeta,
+};
+use kube::{
+  api::{Api, ApiResource, DynamicObject, GroupVersionKind, ListParams},
+  Client,
+};
+use tracing::warn;
+
+use crate::{finding, k8s::checks, version};
+
+/// Provisioner of the in-tree Amazon EBS volume plugin
+const IN_TREE_EBS_PROVISIONER: &str = "kubernetes.io/aws-ebs";
+
+/// Provisioner of the Amazon EBS CSI driver
+const EBS_CSI_PROVISIONER: &str = "ebs.csi.aws.com";
+
+/// Annotation set on dynamically provisioned PersistentVolumes with the name of the provisioner
+const PROVISIONED_BY_ANNOTATION: &str = "pv.kubernetes.io/provisioned-by";
+
+/// Name of the Amazon EKS addon for the Amazon EBS CSI driver
+const EBS_CSI_ADDON: &str = "aws-ebs-csi-driver";
+
+/// Name of the Amazon EBS CSI driver controller Deployment, for both the addon and self-managed installations
+const EBS_CSI_CONTROLLER: &str = "ebs-csi-controller";
+
+/// Annotation on a service account with the IAM role assumed by its pods (IAM roles for service accounts)
+const ROLE_ARN_ANNOTATION: &str = "eks.amazonaws.com/role-arn";
+
+/// Annotation that marks the StorageClass used for PersistentVolumeClaims that do not specify one
+const DEFAULT_CLASS_ANNOTATION: &str = "storageclass.kubernetes.io/is-default-class";
+
+/// StorageClass parameter for the Amazon EBS volume type, for both the in-tree and CSI provisioners
+const VOLUME_TYPE_PARAMETER: &str = "type";
+
+/// Installation of the Amazon EBS CSI driver on the cluster
+#[derive(Debug)]
+pub(crate) struct EbsCsiDriver {
+  /// Either `EKS addon` or `self-managed`
+  pub installation: String,
+  pub healthy: bool,
+  /// IAM role used by the driver controller, if one is set
+  pub role_arn: Option<String>,
+}
+
+/// Returns the self-managed installation of the Amazon EBS CSI driver, if one is found
+async fn get_self_managed_ebs_csi_driver(client: &Client) -> Result<Option<EbsCsiDriver>> {
+  let api: Api<apps::v1::Deployment> = Api::all(client.to_owned());
+  let params = ListParams::default().fields(&format!("metadata.name={EBS_CSI_CONTROLLER}"));
+  let controller = match api.list(&params).await?.items.into_iter().next() {
+    Some(controller) => controller,
+    None => return Ok(None),
+  };
+
+  let namespace = controller.metadata.namespace.to_owned().unwrap_or_default();
+  let service_account = controller
+    .spec
+    .as_ref()
+    .and_then(|spec| spec.template.spec.as_ref())
+    .and_then(|spec| spec.service_account_name.to_owned());
+  let role_arn = match service_account {
+    Some(service_account) => {
+      let api: Api<core::v1::ServiceAccount> = Api::namespaced(client.to_owned(), &namespace);
+      api
+        .get_opt(&service_account)
+        .await?
+        .and_then(|sa| sa.metadata.annotations)
+        .and_then(|annotations| annotations.get(ROLE_ARN_ANNOTATION).cloned())
+    }
+    None => None,
+  };
+
+  Ok(Some(EbsCsiDriver {
+    installation: "self-managed".to_string(),
+    healthy: controller
+      .status
+      .and_then(|status| status.available_replicas)
+      .unwrap_or_default()
+      > 0,
+    role_arn,
+  }))
+}
+
+/// Returns the installation of the Amazon EBS CSI driver, either as an Amazon EKS addon or self-managed
+async fn get_ebs_csi_driver(client: &Client, addons: &[Addon]) -> Result<Option<EbsCsiDriver>> {
+  if let Some(addon) = addons.iter().find(|addon| addon.addon_name() == Some(EBS_CSI_ADDON)) {
+    return Ok(Some(EbsCsiDriver {
+      installation: "EKS addon".to_string(),
+      healthy: addon.status() == Some(&AddonStatus::Active),
+      role_arn: addon.service_account_role_arn().map(ToOwned::to_owned),
+    }));
+  }
+
+  get_self_managed_ebs_csi_driver(client).await
+}
+
+/// Returns true if the PersistentVolume is an in-tree Amazon EBS volume
+fn is_in_tree_ebs_volume(pv: &core::v1::PersistentVolume) -> bool {
+  let provisioned_by = pv
+    .metadata
+    .annotations
+    .as_ref()
+    .and_then(|annotations| annotations.get(PROVISIONED_BY_ANNOTATION))
+    .map(|provisioner| provisioner == IN_TREE_EBS_PROVISIONER)
+    .unwrap_or(false);
+  let aws_ebs = pv
+    .spec
+    .as_ref()
+    .map(|spec| spec.aws_elastic_block_store.is_some())
+    .unwrap_or(false);
+
+  provisioned_by || aws_ebs
+}
+
+/// K8S010 - check for in-tree Amazon EBS StorageClasses and PersistentVolumes, and the readiness of the Amazon EBS CSI driver
+///
+/// Starting with Kubernetes 1.23, operations for in-tree Amazon EBS volumes are redirected to the Amazon EBS CSI driver
+/// which must be installed, healthy, and permitted to manage volumes. In-tree StorageClasses are always reported since
+/// they should be replaced with StorageClasses using the CSI driver; in-tree PersistentVolumes are reported until the
+/// CSI driver is ready to manage them
+pub(crate) fn ebs_csi_migration(
+  storage_classes: &[storage::v1::StorageClass],
+  pvs: &[core::v1::PersistentVolume],
+  driver: &Option<EbsCsiDriver>,
+  target_version: &version::Version,
+) -> Vec<checks::EbsCsiMigration> {
+  let ready = driver.as_ref().map(|driver| driver.healthy).unwrap_or(false);
+  let role_arn = driver.as_ref().and_then(|driver| driver.role_arn.to_owned());
+  let csi_driver = match driver {
+    Some(driver) if driver.healthy => driver.installation.to_owned(),
+    Some(driver) => format!("{} (unhealthy)", driver.installation),
+    None => "not installed".to_string(),
+  };
+
+  let remediation = if !ready && *target_version >= version::Version::new(1, 23) {
+    finding::Remediation::Required
+  } else {
+    finding::Remediation::Recommended
+  };
+  let new_finding = |kind: &str, name: Option<String>| checks::EbsCsiMigration {
+    finding: finding::Finding {
+      code: finding::Code::K8S010,
+      symbol: remediation.symbol(),
+      remediation: remediation.to_owned(),
+    },
+    kind: kind.to_string(),
+    name: name.unwrap_or_default(),
+    csi_driver: csi_driver.to_owned(),
+    iam_role: role_arn.to_owned().unwrap_or_else(|| "-".to_string()),
+  };
+
+  let storage_classes = storage_classes
+    .iter()
+    .filter(|sc| sc.provisioner == IN_TREE_EBS_PROVISIONER)
+    .map(|sc| new_finding("StorageClass", sc.metadata.name.to_owned()));
+
+  let pvs = pvs
+    .iter()
+    .filter(|_| !ready || role_arn.is_none())
+    .filter(|pv| is_in_tree_ebs_volume(pv))
+    .map(|pv| new_finding("PersistentVolume", pv.metadata.name.to_owned()));
+
+  storage_classes.chain(pvs).collect()
+}
+
+/// K8S010 - check the readiness of the cluster for the migration of in-tree Amazon EBS volumes to the Amazon EBS CSI driver
+pub(crate) async fn get_ebs_csi_migration(
+  client: &Client,
+  storage_classes: &[storage::v1::StorageClass],
+  pvs: &[core::v1::PersistentVolume],
+  addons: &[Addon],
+  target_version: &version::Version,
+) -> Result<Vec<checks::EbsCsiMigration>> {
+  let driver = match get_ebs_csi_driver(client, addons).await {
+    Ok(driver) => driver,
+    Err(err) => {
+      warn!("Unable to determine the Amazon EBS CSI driver installation: {err}");
+      return Ok(vec![]);
+    }
+  };
+
+  Ok(ebs_csi_migration(storage_classes, pvs, &driver, target_version))
+}
+
+/// Returns the StorageClasses in the cluster
+pub(crate) async fn get_storage_classes(client: &Client) -> Result<Vec<storage::v1::StorageClass>> {
+  let api: Api<storage::v1::StorageClass> = Api::all(client.to_owned());
+  let sc_list = api.list(&Default::default()).await?;
+
+  Ok(sc_list.items)
+}
+
+/// Returns the PersistentVolumes in the cluster
+pub(crate) async fn get_persistent_volumes(client: &Client) -> Result<Vec<core::v1::PersistentVolume>> {
+  let api: Api<core::v1::PersistentVolume> = Api::all(client.to_owned());
+  let pv_list = api.list(&Default::default()).await?;
+
+  Ok(pv_list.items)
+}
+
+/// Returns the Amazon EBS volume type provisioned by the StorageClass, if it provisions Amazon EBS volumes
+///
+/// When the type is not specified, the in-tree provisioner defaults to `gp2` and the CSI driver defaults to `gp3`
+fn ebs_volume_type(sc: &storage::v1::StorageClass) -> Option<String> {
+  let volume_type = sc
+    .parameters
+    .as_ref()
+    .and_then(|parameters| parameters.get(VOLUME_TYPE_PARAMETER))
+    .map(|volume_type| volume_type.to_lowercase());
+
+  match sc.provisioner.as_str() {
+    IN_TREE_EBS_PROVISIONER => Some(volume_type.unwrap_or_else(|| "gp2".to_string())),
+    EBS_CSI_PROVISIONER => Some(volume_type.unwrap_or_else(|| "gp3".to_string())),
+    _ => None,
+  }
+}
+
+fn is_default_class(sc: &storage::v1::StorageClass) -> bool {
+  sc.metadata
+    .annotations
+    .as_ref()
+    .and_then(|annotations| annotations.get(DEFAULT_CLASS_ANNOTATION))
+    .map(|default| default == "true")
+    .unwrap_or(false)
+}
+
+/// Parse a Kubernetes resource quantity (i.e. - `20Gi`, `100G`) into bytes
+fn parse_quantity(quantity: &str) -> Option<f64> {
+  let quantity = quantity.trim();
+  let split = quantity
+    .find(|c: char| c.is_ascii_alphabetic())
+    .unwrap_or(quantity.len());
+  let (value, suffix) = quantity.split_at(split);
+
+  let multiplier = match suffix {
+    "" => 1.0,
+    "m" => 1e-3,
+    "k" => 1e3,
+    "M" => 1e6,
+    "G" => 1e9,
+    "T" => 1e12,
+    "P" => 1e15,
+    "E" => 1e18,
+    "Ki" => 1024_f64,
+    "Mi" => 1024_f64.powi(2),
+    "Gi" => 1024_f64.powi(3),
+    "Ti" => 1024_f64.powi(4),
+    "Pi" => 1024_f64.powi(5),
+    "Ei" => 1024_f64.powi(6),
+    _ => return None,
+  };
+
+  Some(value.parse::<f64>().ok()? * multiplier)
+}
+
+/// Format bytes in gibibytes, with at most one decimal place
+fn display_gib(bytes: f64) -> String {
+  let gib = (bytes / 1024_f64.powi(3) * 10.0).round() / 10.0;
+  format!("{gib}Gi")
+}
+
+/// Row under which the `gp2` volumes that are not provisioned through an existing StorageClass are reported
+pub(crate) const NO_STORAGE_CLASS: &str = "(no StorageClass)";
+
+/// Returns true if the PersistentVolume is a `gp2` Amazon EBS volume, as determined from the volume itself
+///
+/// In-tree volumes do not record their type and are treated as `gp2`, the default of the in-tree provisioner;
+/// volumes of the Amazon EBS CSI driver are `gp2` when their volume attributes specify it
+fn is_gp2_volume(pv: &core::v1::PersistentVolume) -> bool {
+  let spec = match pv.spec.as_ref() {
+    Some(spec) => spec,
+    None => return false,
+  };
+  let csi_gp2 = spec
+    .csi
+    .as_ref()
+    .filter(|csi| csi.driver == EBS_CSI_PROVISIONER)
+    .and_then(|csi| csi.volume_attributes.as_ref())
+    .and_then(|attributes| attributes.get(VOLUME_TYPE_PARAMETER))
+    .map(|volume_type| volume_type.eq_ignore_ascii_case("gp2"))
+    .unwrap_or(false);
+
+  spec.aws_elastic_block_store.is_some() || csi_gp2
+}
+
+/// K8S015 - check for StorageClasses and PersistentVolumes that provision `gp2` Amazon EBS volumes
+///
+/// `gp3` volumes provide a higher baseline performance at a lower cost than `gp2` volumes. The volumes
+/// are aggregated per StorageClass and namespace (of the bound claim) to plan their migration to `gp3`.
+/// `gp2` volumes without a StorageClass, or whose StorageClass no longer exists (i.e. - statically provisioned,
+/// or the StorageClass was deleted or renamed), are aggregated under `NO_STORAGE_CLASS`
+pub(crate) fn gp2_volumes(
+  storage_classes: &[storage::v1::StorageClass],
+  pvs: &[core::v1::PersistentVolume],
+) -> Vec<checks::Gp2Volume> {
+  let remediation = finding::Remediation::Recommended;
+  let gp2_classes: Vec<&storage::v1::StorageClass> = storage_classes
+    .iter()
+    .filter(|sc| ebs_volume_type(sc).as_deref() == Some("gp2"))
+    .collect();
+  let existing: BTreeSet<&str> = storage_classes
+    .iter()
+    .filter_map(|sc| sc.metadata.name.as_deref())
+    .collect();
+
+  // Volumes and their aggregate capacity, keyed by the StorageClass they are reported under and their namespace
+  let mut aggregates: BTreeMap<&str, BTreeMap<String, (usize, f64)>> = BTreeMap::new();
+  for pv in pvs {
+    let spec = match pv.spec.as_ref() {
+      Some(spec) => spec,
+      None => continue,
+    };
+    let storage_class = match spec.storage_class_name.as_deref() {
+      Some(name) if existing.contains(name) => {
+        match gp2_classes.iter().find(|sc| sc.metadata.name.as_deref() == Some(name)) {
+          Some(_) => name,
+          None => continue,
+        }
+      }
+      _ if is_gp2_volume(pv) => NO_STORAGE_CLASS,
+      _ => continue,
+    };
+    let namespace = spec
+      .claim_ref
+      .as_ref()
+      .and_then(|claim| claim.namespace.to_owned())
+      .unwrap_or_default();
+    let capacity = spec
+      .capacity
+      .as_ref()
+      .and_then(|capacity| capacity.get("storage"))
+      .and_then(|storage| parse_quantity(&storage.0))
+      .unwrap_or_default();
+
+    let (volumes, bytes) = aggregates
+      .entry(storage_class)
+      .or_default()
+      .entry(namespace)
+      .or_default();
+    *volumes += 1;
+    *bytes += capacity;
+  }
+
+  let new_finding =
+    |storage_class: &str, default: bool, namespace: String, (volumes, bytes): (usize, f64)| checks::Gp2Volume {
+      finding: finding::Finding {
+        code: finding::Code::K8S015,
+        symbol: remediation.symbol(),
+        remediation: remediation.to_owned(),
+      },
+      storage_class: storage_class.to_owned(),
+      default,
+      namespace,
+      volumes,
+      capacity: display_gib(bytes),
+    };
+
+  let mut findings = Vec::new();
+  for sc in gp2_classes {
+    let name = sc.metadata.name.as_deref().unwrap_or_default();
+    let mut namespaces = aggregates.remove(name).unwrap_or_default();
+    // StorageClasses without volumes are still reported since new volumes would be provisioned as `gp2`
+    if namespaces.is_empty() {
+      namespaces.insert(String::new(), (0, 0.0));
+    }
+
+    findings.extend(
+      namespaces
+        .into_iter()
+        .map(|(namespace, aggregate)| new_finding(name, is_default_class(sc), namespace, aggregate)),
+    );
+  }
+
+  findings.extend(
+    aggregates
+      .remove(NO_STORAGE_CLASS)
+      .unwrap_or_default()
+      .into_iter()
+      .map(|(namespace, aggregate)| new_finding(NO_STORAGE_CLASS, false, namespace, aggregate)),
+  );
+
+  findings
+}
+
+/// Returns the name of the `gp3` equivalent of a `gp2` StorageClass
+///
+/// The `gp2` in the name is replaced by `gp3`; when that name is already taken, `-gp3` is appended to
+/// the name instead. Returns `None` when both names are taken so that existing StorageClasses are never replaced
+fn gp3_name(name: &str, taken: &BTreeSet<String>) -> Option<String> {
+  let mut candidates = Vec::new();
+  if name.contains("gp2") {
+    candidates.push(name.replace("gp2", "gp3"));
+  }
+  candidates.push(format!("{name}-gp3"));
+
+  candidates.into_iter().find(|candidate| !taken.contains(candidate))
+}
+
+/// Returns the `gp3` equivalent, provisioned by the Amazon EBS CSI driver, of a `gp2` StorageClass
+fn gp3_storage_class(sc: &storage::v1::StorageClass, name: String) -> storage::v1::StorageClass {
+  let mut parameters = sc.parameters.to_owned().unwrap_or_default();
+  parameters.insert(VOLUME_TYPE_PARAMETER.to_string(), "gp3".to_string());
+  // The CSI driver uses the standard CSI parameter for the filesystem type
+  if let Some(fs_type) = parameters.remove("fsType") {
+    parameters.insert("csi.storage.k8s.io/fstype".to_string(), fs_type);
+  }
+
+  storage::v1::StorageClass {
+    metadata: ObjectMeta {
+      name: Some(name),
+      ..Default::default()
+    },
+    provisioner: EBS_CSI_PROVISIONER.to_string(),
+    parameters: Some(parameters),
+    reclaim_policy: sc.reclaim_policy.to_owned(),
+    volume_binding_mode: sc.volume_binding_mode.to_owned(),
+    allow_volume_expansion: sc.allow_volume_expansion,
+    allowed_topologies: sc.allowed_topologies.to_owned(),
+    mount_options: sc.mount_options.to_owned(),
+  }
+}
+
+/// Render the manifests of the `gp3` equivalents of the `gp2` StorageClasses provided
+///
+/// A `gp3` StorageClass is created for each `gp2` StorageClass, along with a VolumeAttributesClass
+/// that can be set on existing PersistentVolumeClaims provisioned by the Amazon EBS CSI driver to
+/// modify their volumes to `gp3` in place (Kubernetes v1.31+ with the `VolumeAttributesClass` feature enabled)
+///
+/// StorageClasses and VolumeAttributesClasses are immutable, so the names of the existing classes are never reused
+pub(crate) fn gp3_manifests(
+  storage_classes: &[storage::v1::StorageClass],
+  volume_attributes_classes: &BTreeSet<String>,
+) -> Result<String> {
+  let mut taken: BTreeSet<String> = storage_classes
+    .iter()
+    .filter_map(|sc| sc.metadata.name.to_owned())
+    .collect();
+  let mut documents = Vec::new();
+
+  for sc in storage_classes
+    .iter()
+    .filter(|sc| ebs_volume_type(sc).as_deref() == Some("gp2"))
+  {
+    let name = sc.metadata.name.as_deref().unwrap_or("gp2");
+    match gp3_name(name, &taken) {
+      Some(gp3) => {
+        taken.insert(gp3.to_owned());
+        documents.push(serde_yaml::to_string(&gp3_storage_class(sc, gp3))?);
+      }
+      None => warn!("Unable to name the gp3 equivalent of StorageClass {name}, the candidate names are taken"),
+    }
+  }
+
+  if !documents.is_empty() {
+    match gp3_name("gp2", volume_attributes_classes) {
+      Some(name) => documents.push(serde_yaml::to_string(&serde_json::json!({
+        "apiVersion": "storage.k8s.io/v1beta1",
+        "kind": "VolumeAttributesClass",
+        "metadata": { "name": name },
+        "driverName": EBS_CSI_PROVISIONER,
+        "parameters": { VOLUME_TYPE_PARAMETER: "gp3" },
+      }))?),
+      None => warn!("Unable to name the gp3 VolumeAttributesClass, the candidate names are taken"),
+    }
+  }
+
+  Ok(documents.iter().map(|document| format!("---\n{document}")).collect())
+}
+
+/// Returns the names of the VolumeAttributesClasses in the cluster
+///
+/// VolumeAttributesClasses are not served prior to Kubernetes v1.31 (or without the feature enabled), in which case there are none
+async fn get_volume_attributes_classes(client: &Client) -> Result<BTreeSet<String>> {
+  let gvk = GroupVersionKind::gvk("storage.k8s.io", "v1beta1", "VolumeAttributesClass");
+  let api: Api<DynamicObject> = Api::all_with(
+    client.to_owned(),
+    &ApiResource::from_gvk_with_plural(&gvk, "volumeattributesclasses"),
+  );
+
+  match api.list_metadata(&Default::default()).await {
+    Ok(list) => Ok(
+      list
+        .items
+        .into_iter()
+        .filter_map(|object| object.metadata.name)
+        .collect(),
+    ),
+    Err(kube::Error::Api(err)) if err.code == 404 => Ok(BTreeSet::new()),
+    Err(err) => Err(err.into()),
+  }
+}
+
+/// Returns the manifests of the `gp3` equivalents of the `gp2` StorageClasses in the cluster
+pub async fn get_gp3_manifests(client: &Client) -> Result<String> {
+  let storage_classes = get_storage_classes(client).await?;
+  let volume_attributes_classes = get_volume_attributes_classes(client).await?;
+
+  gp3_manifests(&storage_classes, &volume_attributes_classes)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::k8s::resources;
+
+  #[test]
+  fn can_check_ebs_csi_migration() {
+    let storage_classes: Vec<storage::v1::StorageClass> = serde_yaml::from_str(
+      r#"
+- metadata: {name: gp2}
+  provisioner: kubernetes.io/aws-ebs
+- metadata: {name: gp3}
+  provisioner: ebs.csi.aws.com
+"#,
+    )
+    .unwrap();
+    let pvs: Vec<core::v1::PersistentVolume> = serde_yaml::from_str(
+      r#"
+- metadata: {name: provisioned, annotations: {pv.kubernetes.io/provisioned-by: kubernetes.io/aws-ebs}}
+- metadata: {name: static}
+  spec: {awsElasticBlockStore: {volumeID: vol-0123456789abcdef0}}
+- metadata: {name: csi, annotations: {pv.kubernetes.io/provisioned-by: ebs.csi.aws.com}}
+"#,
+    )
+    .unwrap();
+    let driver = |healthy: bool, role_arn: Option<&str>| {
+      Some(EbsCsiDriver {
+        installation: "EKS addon".to_string(),
+        healthy,
+        role_arn: role_arn.map(ToOwned::to_owned),
+      })
+    };
+
+    let findings = ebs_csi_migration(&storage_classes, &pvs, &None, &version::Version::new(1, 23));
+    let names: Vec<&str> = findings.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, vec!["gp2", "provisioned", "static"]);
+    assert!(findings
+      .iter()
+      .all(|f| f.finding.remediation.to_string() == "Required" && f.csi_driver == "not installed"));
+
+    let findings = ebs_csi_migration(&storage_classes, &pvs, &None, &version::Version::new(1, 22));
+    assert!(findings
+      .iter()
+      .all(|f| f.finding.remediation.to_string() == "Recommended"));
+
+    let findings = ebs_csi_migration(
+      &storage_classes,
+      &pvs,
+      &driver(false, None),
+      &version::Version::new(1, 23),
+    );
+    assert_eq!(findings[0].csi_driver, "EKS addon (unhealthy)");
+    assert_eq!(findings[0].finding.remediation.to_string(), "Required");
+
+    let findings = ebs_csi_migration(
+      &storage_classes,
+      &pvs,
+      &driver(true, None),
+      &version::Version::new(1, 23),
+    );
+    assert_eq!(findings.len(), 3);
+    assert_eq!(findings[0].finding.remediation.to_string(), "Recommended");
+
+    // Once the driver is ready, only the in-tree StorageClasses remain to be replaced
+    let role_arn = "arn:aws:iam::111122223333:role/ebs-csi";
+    let findings = ebs_csi_migration(
+      &storage_classes,
+      &pvs,
+      &driver(true, Some(role_arn)),
+      &version::Version::new(1, 23),
+    );
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].iam_role, role_arn);
+  }
+
+  #[test]
+  fn can_check_gp2_volumes() {
+    let storage_classes: Vec<storage::v1::StorageClass> = serde_yaml::from_str(
+      r#"
+- metadata: {name: gp2, annotations: {storageclass.kubernetes.io/is-default-class: "true"}}
+  provisioner: kubernetes.io/aws-ebs
+  parameters: {fsType: ext4}
+  volumeBindingMode: WaitForFirstConsumer
+- metadata: {name: csi-gp2}
+  provisioner: ebs.csi.aws.com
+  parameters: {type: gp2}
+- metadata: {name: io2}
+  provisioner: kubernetes.io/aws-ebs
+  parameters: {type: io2}
+- metadata: {name: gp3}
+  provisioner: ebs.csi.aws.com
+"#,
+    )
+    .unwrap();
+    let pvs: Vec<core::v1::PersistentVolume> = serde_yaml::from_str(
+      r#"
+- metadata: {name: a}
+  spec: {storageClassName: gp2, capacity: {storage: 20Gi}, claimRef: {namespace: web}}
+- metadata: {name: b}
+  spec: {storageClassName: gp2, capacity: {storage: 5368709120}, claimRef: {namespace: web}}
+- metadata: {name: c}
+  spec: {storageClassName: gp2, capacity: {storage: 1Ti}, claimRef: {namespace: db}}
+- metadata: {name: d}
+  spec: {storageClassName: gp3, capacity: {storage: 1Ti}, claimRef: {namespace: db}}
+- metadata: {name: deleted}
+  spec:
+    storageClassName: standard
+    awsElasticBlockStore: {volumeID: vol-0123456789abcdef0}
+    capacity: {storage: 10Gi}
+    claimRef: {namespace: web}
+- metadata: {name: static}
+  spec:
+    csi: {driver: ebs.csi.aws.com, volumeHandle: vol-0123456789abcdef1, volumeAttributes: {type: gp2}}
+    capacity: {storage: 30Gi}
+    claimRef: {namespace: db}
+- metadata: {name: static-gp3}
+  spec:
+    csi: {driver: ebs.csi.aws.com, volumeHandle: vol-0123456789abcdef2, volumeAttributes: {type: gp3}}
+    capacity: {storage: 30Gi}
+- metadata: {name: io2}
+  spec:
+    storageClassName: io2
+    awsElasticBlockStore: {volumeID: vol-0123456789abcdef3}
+    capacity: {storage: 30Gi}
+"#,
+    )
+    .unwrap();
+
+    let findings = gp2_volumes(&storage_classes, &pvs);
+    let summary: Vec<(&str, bool, &str, usize, &str)> = findings
+      .iter()
+      .map(|f| {
+        (
+          f.storage_class.as_str(),
+          f.default,
+          f.namespace.as_str(),
+          f.volumes,
+          f.capacity.as_str(),
+        )
+      })
+      .collect();
+    assert_eq!(
+      summary,
+      vec![
+        ("gp2", true, "db", 1, "1024Gi"),
+        ("gp2", true, "web", 2, "25Gi"),
+        ("csi-gp2", false, "", 0, "0Gi"),
+        // Statically provisioned, or the StorageClass was deleted
+        ("(no StorageClass)", false, "db", 1, "30Gi"),
+        ("(no StorageClass)", false, "web", 1, "10Gi"),
+      ]
+    );
+
+    let manifests = gp3_manifests(&storage_classes, &BTreeSet::new()).unwrap();
+    let manifests = resources::Manifest::parse("-", &manifests).unwrap();
+    let kinds: Vec<(&str, &str)> = manifests.iter().map(|m| (m.kind(), m.name())).collect();
+    assert_eq!(
+      kinds,
+      vec![
+        ("StorageClass", "gp2-gp3"),
+        ("StorageClass", "csi-gp3"),
+        ("VolumeAttributesClass", "gp3")
+      ]
+    );
+    assert_eq!(manifests[0].value["provisioner"], "ebs.csi.aws.com");
+    assert_eq!(manifests[0].value["parameters"]["csi.storage.k8s.io/fstype"], "ext4");
+    assert_eq!(manifests[0].value["volumeBindingMode"], "WaitForFirstConsumer");
+
+    // An existing VolumeAttributesClass is never replaced
+    let taken = BTreeSet::from(["gp3".to_string()]);
+    let manifests = gp3_manifests(&storage_classes, &taken).unwrap();
+    let manifests = resources::Manifest::parse("-", &manifests).unwrap();
+    assert_eq!(manifests.last().map(|m| m.name()), Some("gp2-gp3"));
+  }
+
+  #[test]
+  fn can_name_gp3_storage_classes() {
+    let taken = |names: &[&str]| names.iter().map(|name| name.to_string()).collect::<BTreeSet<_>>();
+
+    assert_eq!(gp3_name("gp2", &taken(&["gp2"])).as_deref(), Some("gp3"));
+    assert_eq!(
+      gp3_name("standard", &taken(&["standard"])).as_deref(),
+      Some("standard-gp3")
+    );
+    // An existing StorageClass is never replaced
+    assert_eq!(gp3_name("gp2", &taken(&["gp2", "gp3"])).as_deref(), Some("gp2-gp3"));
+    assert_eq!(gp3_name("gp2", &taken(&["gp2", "gp3", "gp2-gp3"])), None);
+  }
+}
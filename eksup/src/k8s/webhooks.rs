@@ -0,0 +1,444 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::Result;
+use k8s_openapi::{
+  api::{admissionregistration::v1 as admission, core},
+  apimachinery::pkg::apis::meta::v1::LabelSelector,
+};
+use kube::{api::Api, Client};
+use tracing::warn;
+
+use crate::{
+  finding,
+  k8s::{checks, resources},
+};
+
+/// Namespace of the cluster components that must be able to start while nodes are replaced
+const KUBE_SYSTEM: &str = "kube-system";
+
+/// Label set by the API server on every namespace with the name of the namespace
+const NAMESPACE_NAME_LABEL: &str = "kubernetes.io/metadata.name";
+
+/// Timeout, in seconds, above which a webhook is considered to have a long timeout (the default is 10s)
+const MAX_TIMEOUT_SECONDS: i32 = 10;
+
+/// The fields common to validating and mutating admission webhooks that are relevant to the check
+#[derive(Debug)]
+struct Webhook {
+  /// Either `ValidatingWebhookConfiguration` or `MutatingWebhookConfiguration`
+  kind: &'static str,
+  configuration: String,
+  name: String,
+  failure_policy: Option<String>,
+  timeout_seconds: Option<i32>,
+  service: Option<admission::ServiceReference>,
+  namespace_selector: Option<LabelSelector>,
+  rules: Vec<admission::RuleWithOperations>,
+}
+
+impl Webhook {
+  /// Webhooks that fail closed reject the requests they intercept whenever they cannot be reached
+  ///
+  /// `Fail` is the default failure policy of `admissionregistration.k8s.io/v1` webhooks
+  fn fails_closed(&self) -> bool {
+    self.failure_policy.as_deref().unwrap_or("Fail") == "Fail"
+  }
+
+  /// Returns true if any of the rules of the webhook intercept the core group resource provided
+  ///
+  /// Rules that only match subresources (i.e. - `pods/exec`, `pods/*`) do not intercept the resource itself
+  fn intercepts_core(&self, resource: &str) -> bool {
+    self.rules.iter().any(|rule| {
+      let core_group = rule
+        .api_groups
+        .iter()
+        .flatten()
+        .any(|group| group.is_empty() || group == "*");
+      core_group
+        && rule
+          .resources
+          .iter()
+          .flatten()
+          .any(|r| r == resource || r == "*" || r == "*/*")
+    })
+  }
+
+  /// Returns true if any of the rules of the webhook intercept namespaced resources
+  fn intercepts_namespaced(&self) -> bool {
+    self
+      .rules
+      .iter()
+      .any(|rule| rule.scope.as_deref().unwrap_or("*") != "Cluster")
+  }
+}
+
+/// Returns the validating and mutating admission webhooks registered in the cluster
+async fn get_webhooks(client: &Client) -> Result<Vec<Webhook>> {
+  let mut webhooks = Vec::new();
+
+  let api: Api<admission::ValidatingWebhookConfiguration> = Api::all(client.to_owned());
+  for configuration in api.list(&Default::default()).await?.items {
+    let name = configuration.metadata.name.unwrap_or_default();
+    webhooks.extend(configuration.webhooks.into_iter().flatten().map(|webhook| Webhook {
+      kind: "ValidatingWebhookConfiguration",
+      configuration: name.to_owned(),
+      name: webhook.name,
+      failure_policy: webhook.failure_policy,
+      timeout_seconds: webhook.timeout_seconds,
+      service: webhook.client_config.service,
+      namespace_selector: webhook.namespace_selector,
+      rules: webhook.rules.unwrap_or_default(),
+    }));
+  }
+
+  let api: Api<admission::MutatingWebhookConfiguration> = Api::all(client.to_owned());
+  for configuration in api.list(&Default::default()).await?.items {
+    let name = configuration.metadata.name.unwrap_or_default();
+    webhooks.extend(configuration.webhooks.into_iter().flatten().map(|webhook| Webhook {
+      kind: "MutatingWebhookConfiguration",
+      configuration: name.to_owned(),
+      name: webhook.name,
+      failure_policy: webhook.failure_policy,
+      timeout_seconds: webhook.timeout_seconds,
+      service: webhook.client_config.service,
+      namespace_selector: webhook.namespace_selector,
+      rules: webhook.rules.unwrap_or_default(),
+    }));
+  }
+
+  Ok(webhooks)
+}
+
+/// Endpoints of Services, keyed by the namespace and name of the Service
+pub(crate) type ServiceEndpoints = BTreeMap<(String, String), core::v1::Endpoints>;
+
+/// Returns the Endpoints of the Services provided, as their namespace and name
+///
+/// Services that do not exist are omitted, and are therefore treated as having no ready endpoints
+pub(crate) async fn get_endpoints(client: &Client, services: BTreeSet<(String, String)>) -> Result<ServiceEndpoints> {
+  let mut endpoints = BTreeMap::new();
+  for (namespace, name) in services {
+    let api: Api<core::v1::Endpoints> = Api::namespaced(client.to_owned(), &namespace);
+    if let Some(endpoint) = api.get_opt(&name).await? {
+      endpoints.insert((namespace, name), endpoint);
+    }
+  }
+
+  Ok(endpoints)
+}
+
+/// Returns the ready endpoint addresses of the Service
+pub(crate) fn ready_addresses<'a>(
+  endpoints: &'a ServiceEndpoints,
+  namespace: &str,
+  name: &str,
+) -> Vec<&'a core::v1::EndpointAddress> {
+  endpoints
+    .get(&(namespace.to_owned(), name.to_owned()))
+    .and_then(|endpoints| endpoints.subsets.as_ref())
+    .into_iter()
+    .flatten()
+    .flat_map(|subset| subset.addresses.iter().flatten())
+    .collect()
+}
+
+/// Returns the labels of the `kube-system` namespace, used to evaluate the namespace selectors of the webhooks
+async fn get_kube_system_labels(client: &Client) -> Result<BTreeMap<String, String>> {
+  let api: Api<core::v1::Namespace> = Api::all(client.to_owned());
+  let mut labels = api
+    .get_opt(KUBE_SYSTEM)
+    .await?
+    .and_then(|namespace| namespace.metadata.labels)
+    .unwrap_or_default();
+  labels.insert(NAMESPACE_NAME_LABEL.to_string(), KUBE_SYSTEM.to_string());
+
+  Ok(labels)
+}
+
+/// K8S019 - check the admission webhooks that fail closed for issues that can deadlock the cluster
+///
+/// - Remediation is required when the Service backing the webhook has no ready endpoints, or
+///   all of its ready endpoints are on a single node
+/// - Remediation is recommended when all of the ready endpoints are in a single nodegroup, the webhook
+///   intercepts nodes, intercepts pods or other objects in `kube-system` without excluding it through
+///   its namespace selector, or has a long timeout
+///
+/// Webhooks with a `failurePolicy` of `Ignore` are not reported
+fn check_webhooks(
+  webhooks: &[Webhook],
+  endpoints: &ServiceEndpoints,
+  nodes: &[resources::Node],
+  kube_system_labels: &BTreeMap<String, String>,
+) -> Vec<checks::AdmissionWebhook> {
+  webhooks
+    .iter()
+    .filter(|webhook| webhook.fails_closed())
+    .filter_map(|webhook| {
+      let mut required = Vec::new();
+      let mut recommended = Vec::new();
+
+      // Webhooks configured with a URL are served outside of the cluster and are not affected by node replacement
+      if let Some(service) = &webhook.service {
+        let ready = ready_addresses(endpoints, &service.namespace, &service.name);
+        // Addresses without a node (i.e. - manually managed or external endpoints) remain available while nodes
+        // are replaced, so the placement of the endpoints is only evaluated when every ready address is on a node
+        let ready_nodes: Option<BTreeSet<&str>> = ready.iter().map(|address| address.node_name.as_deref()).collect();
+        let ready_nodes = ready_nodes.unwrap_or_default();
+        // Nodes that cannot be found, and therefore their nodegroup determined, are treated as an unknown nodegroup
+        let nodegroups: BTreeSet<String> = ready_nodes
+          .iter()
+          .map(|name| {
+            nodes
+              .iter()
+              .find(|node| node.name == *name)
+              .map(checks::get_nodegroup)
+              .unwrap_or_else(|| "-".to_string())
+          })
+          .collect();
+
+        if ready.is_empty() {
+          required.push("Service has no ready endpoints".to_string());
+        } else if let (1, Some(node)) = (ready_nodes.len(), ready_nodes.first()) {
+          required.push(format!("All endpoints on node {node}"));
+        } else if let (1, Some(nodegroup)) = (nodegroups.len(), nodegroups.first()) {
+          if nodegroup != "-" {
+            recommended.push(format!("All endpoints in nodegroup {nodegroup}"));
+          }
+        }
+      }
+
+      // The `objectSelector` is not evaluated since it matches the labels of the individual objects, which cannot
+      // be known ahead of time for the pods created while nodes are replaced; the `namespaceSelector` is the
+      // recommended way to exempt the cluster components in `kube-system`
+      let excludes_kube_system = webhook
+        .namespace_selector
+        .as_ref()
+        .map(|selector| !resources::selector_matches(selector, kube_system_labels))
+        .unwrap_or(false);
+      // Nodes are cluster-scoped and are therefore intercepted regardless of the namespace selector
+      if webhook.intercepts_core("pods") && !excludes_kube_system {
+        recommended.push("Intercepts pods".to_string());
+      }
+      if webhook.intercepts_core("nodes") {
+        recommended.push("Intercepts nodes".to_string());
+      }
+
+      if webhook.intercepts_namespaced() && !excludes_kube_system {
+        recommended.push(format!("Intercepts {KUBE_SYSTEM} objects"));
+      }
+
+      let timeout = webhook.timeout_seconds.unwrap_or(MAX_TIMEOUT_SECONDS);
+      if timeout > MAX_TIMEOUT_SECONDS {
+        recommended.push(format!("timeoutSeconds of {timeout}"));
+      }
+
+      let remediation = match (required.is_empty(), recommended.is_empty()) {
+        (false, _) => finding::Remediation::Required,
+        (true, false) => finding::Remediation::Recommended,
+        (true, true) => return None,
+      };
+
+      Some(checks::AdmissionWebhook {
+        finding: finding::Finding {
+          code: finding::Code::K8S019,
+          symbol: remediation.symbol(),
+          remediation,
+        },
+        kind: webhook.kind.to_string(),
+        configuration: webhook.configuration.to_owned(),
+        name: webhook.name.to_owned(),
+        service: webhook
+          .service
+          .as_ref()
+          .map(|service| format!("{}/{}", service.namespace, service.name))
+          .unwrap_or_else(|| "-".to_string()),
+        issues: required.into_iter().chain(recommended).collect(),
+      })
+    })
+    .collect()
+}
+
+/// K8S019 - check the admission webhooks registered in the cluster
+pub(crate) async fn admission_webhooks(
+  client: &Client,
+  nodes: &[resources::Node],
+) -> Result<Vec<checks::AdmissionWebhook>> {
+  let webhooks = match get_webhooks(client).await {
+    Ok(webhooks) => webhooks,
+    Err(err) => {
+      warn!("Unable to list admission webhook configurations: {err}");
+      return Ok(vec![]);
+    }
+  };
+  let services = webhooks
+    .iter()
+    .filter_map(|webhook| webhook.service.as_ref())
+    .map(|service| (service.namespace.to_owned(), service.name.to_owned()))
+    .collect();
+  // Without the endpoints every webhook would be reported as unavailable, so the check is skipped instead
+  let endpoints = match get_endpoints(client, services).await {
+    Ok(endpoints) => endpoints,
+    Err(err) => {
+      warn!("Unable to get the endpoints of the admission webhook services: {err}");
+      return Ok(vec![]);
+    }
+  };
+  let kube_system_labels = match get_kube_system_labels(client).await {
+    Ok(labels) => labels,
+    Err(err) => {
+      warn!("Unable to get the {KUBE_SYSTEM} namespace: {err}");
+      BTreeMap::from([(NAMESPACE_NAME_LABEL.to_string(), KUBE_SYSTEM.to_string())])
+    }
+  };
+
+  Ok(check_webhooks(&webhooks, &endpoints, nodes, &kube_system_labels))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn webhook(name: &str, service: Option<&str>, yaml: &str) -> Webhook {
+    let webhook: admission::ValidatingWebhook = serde_yaml::from_str(yaml).unwrap();
+    Webhook {
+      kind: "ValidatingWebhookConfiguration",
+      configuration: "example".to_string(),
+      name: name.to_string(),
+      failure_policy: webhook.failure_policy,
+      timeout_seconds: webhook.timeout_seconds,
+      service: service.map(|name| admission::ServiceReference {
+        namespace: "webhooks".to_string(),
+        name: name.to_string(),
+        ..Default::default()
+      }),
+      namespace_selector: webhook.namespace_selector,
+      rules: webhook.rules.unwrap_or_default(),
+    }
+  }
+
+  #[test]
+  fn can_check_webhooks() {
+    let base = "{name: x, sideEffects: None, admissionReviewVersions: [v1], clientConfig: {}";
+    let excluded = "namespaceSelector: {matchExpressions: [{key: kubernetes.io/metadata.name, operator: NotIn, values: [kube-system]}]}";
+    let rules = "rules: [{apiGroups: [apps], apiVersions: [v1], operations: [CREATE], resources: [deployments]}]";
+    let webhooks = vec![
+      webhook("ignored", Some("down"), &format!("{base}, failurePolicy: Ignore}}")),
+      webhook("down", Some("down"), &format!("{base}, {excluded}, {rules}}}")),
+      webhook("single-node", Some("single-node"), &format!("{base}, {excluded}, {rules}}}")),
+      webhook("nodegroup", Some("nodegroup"), &format!("{base}, {excluded}, {rules}}}")),
+      webhook("healthy", Some("healthy"), &format!("{base}, {excluded}, {rules}}}")),
+      webhook("external", Some("external"), &format!("{base}, {excluded}, {rules}}}")),
+      webhook("mixed", Some("mixed"), &format!("{base}, {excluded}, {rules}}}")),
+      webhook(
+        "pods",
+        None,
+        &format!("{base}, rules: [{{apiGroups: [''], apiVersions: [v1], operations: [CREATE], resources: [pods]}}]}}"),
+      ),
+      webhook(
+        "pods-excluded",
+        None,
+        &format!("{base}, {excluded}, rules: [{{apiGroups: [''], apiVersions: [v1], operations: [CREATE], resources: [pods]}}]}}"),
+      ),
+      webhook(
+        "everything",
+        None,
+        &format!("{base}, {excluded}, rules: [{{apiGroups: ['*'], apiVersions: ['*'], operations: ['*'], resources: ['*/*']}}]}}"),
+      ),
+      webhook(
+        "other-group",
+        None,
+        &format!("{base}, {excluded}, rules: [{{apiGroups: [apps], apiVersions: [v1], operations: [CREATE], resources: ['*']}}]}}"),
+      ),
+      webhook(
+        "subresources",
+        None,
+        &format!("{base}, timeoutSeconds: 30, rules: [{{apiGroups: [''], apiVersions: [v1], operations: [CREATE], resources: [pods/*]}}]}}"),
+      ),
+    ];
+
+    let endpoints: ServiceEndpoints = [
+      ("down", "[{notReadyAddresses: [{ip: 10.0.0.1, nodeName: a1}]}]"),
+      (
+        "single-node",
+        "[{addresses: [{ip: 10.0.0.1, nodeName: a1}, {ip: 10.0.0.2, nodeName: a1}]}]",
+      ),
+      (
+        "nodegroup",
+        "[{addresses: [{ip: 10.0.0.1, nodeName: a1}, {ip: 10.0.0.2, nodeName: a2}]}]",
+      ),
+      (
+        "healthy",
+        "[{addresses: [{ip: 10.0.0.1, nodeName: a1}, {ip: 10.0.0.2, nodeName: b1}]}]",
+      ),
+      ("external", "[{addresses: [{ip: 192.168.0.1}, {ip: 192.168.0.2}]}]"),
+      (
+        "mixed",
+        "[{addresses: [{ip: 10.0.0.1, nodeName: a1}, {ip: 192.168.0.1}]}]",
+      ),
+    ]
+    .into_iter()
+    .map(|(name, subsets)| {
+      let endpoints = core::v1::Endpoints {
+        subsets: serde_yaml::from_str(subsets).unwrap(),
+        ..Default::default()
+      };
+      (("webhooks".to_string(), name.to_string()), endpoints)
+    })
+    .collect();
+
+    let nodes: Vec<resources::Node> = [("a1", "a"), ("a2", "a"), ("b1", "b")]
+      .into_iter()
+      .map(|(name, nodegroup)| resources::Node {
+        name: name.to_string(),
+        labels: Some(BTreeMap::from([(
+          "eks.amazonaws.com/nodegroup".to_string(),
+          nodegroup.to_string(),
+        )])),
+        kubelet_version: "v1.24.9-eks-49d8fe8".to_string(),
+        version: crate::version::Version::new(1, 24),
+        conditions: BTreeMap::new(),
+        unschedulable: false,
+        taints: vec![],
+        created: None,
+      })
+      .collect();
+    let kube_system_labels = BTreeMap::from([(NAMESPACE_NAME_LABEL.to_string(), KUBE_SYSTEM.to_string())]);
+
+    let findings = check_webhooks(&webhooks, &endpoints, &nodes, &kube_system_labels);
+    let issues: Vec<(&str, String, Vec<&str>)> = findings
+      .iter()
+      .map(|f| {
+        (
+          f.name.as_str(),
+          f.finding.remediation.to_string(),
+          f.issues.iter().map(String::as_str).collect(),
+        )
+      })
+      .collect();
+    assert_eq!(
+      issues,
+      vec![
+        ("down", "Required".to_string(), vec!["Service has no ready endpoints"]),
+        ("single-node", "Required".to_string(), vec!["All endpoints on node a1"]),
+        (
+          "nodegroup",
+          "Recommended".to_string(),
+          vec!["All endpoints in nodegroup a"]
+        ),
+        (
+          "pods",
+          "Recommended".to_string(),
+          vec!["Intercepts pods", "Intercepts kube-system objects"]
+        ),
+        // Pods are only reported when kube-system is not excluded, whereas nodes are cluster-scoped
+        ("everything", "Recommended".to_string(), vec!["Intercepts nodes"]),
+        // Only subresources of pods (i.e. - `pods/exec`) are intercepted
+        (
+          "subresources",
+          "Recommended".to_string(),
+          vec!["Intercepts kube-system objects", "timeoutSeconds of 30"]
+        ),
+      ]
+    );
+  }
+}
@@ -1,19 +1,25 @@
 mod analysis;
+mod calendar;
 mod eks;
 mod finding;
+mod fleet;
 mod k8s;
+mod manifests;
 mod output;
 mod playbook;
+mod scan;
 mod version;
 
-use std::{env, process, str};
+use std::{env, fs, process, str, time::Duration};
 
-use anyhow::{Context, Result};
-use aws_config::meta::region::RegionProviderChain;
+use anyhow::{bail, Context, Result};
+use aws_config::{meta::region::RegionProviderChain, profile::ProfileFileRegionProvider};
 use aws_types::region::Region;
 use clap::{Args, Parser, Subcommand};
 use clap_verbosity_flag::Verbosity;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
+use tracing::warn;
 
 fn get_styles() -> clap::builder::Styles {
   clap::builder::Styles::styled()
@@ -59,19 +65,57 @@ pub enum Commands {
   Analyze(Analysis),
   #[command(arg_required_else_help = true)]
   Create(Create),
+  Calendar(Calendar),
+  #[command(arg_required_else_help = true)]
+  Scan(Scan),
 }
 
 /// Analyze an Amazon EKS cluster for potential upgrade issues
 #[derive(Args, Debug, Serialize, Deserialize)]
 pub struct Analysis {
   /// The name of the cluster to analyze
-  #[arg(short, long, alias = "cluster-name", value_enum)]
-  pub cluster: String,
+  #[arg(
+    short,
+    long,
+    alias = "cluster-name",
+    value_enum,
+    required_unless_present = "all_clusters",
+    conflicts_with = "all_clusters"
+  )]
+  pub cluster: Option<String>,
+
+  /// Analyze all clusters in the region and report a summary of the findings for each cluster
+  #[arg(long)]
+  pub all_clusters: bool,
+
+  /// Only analyze clusters whose name matches the regular expression
+  #[arg(long, requires = "all_clusters")]
+  pub name_filter: Option<String>,
+
+  /// Only analyze clusters with the given tag, in the form of `key=value` (may be repeated)
+  #[arg(long = "tag", value_parser = fleet::parse_tag, requires = "all_clusters")]
+  pub tags: Vec<(String, String)>,
 
   /// The AWS region where the cluster is provisioned
   #[arg(short, long)]
   pub region: Option<String>,
 
+  /// The AWS regions to analyze clusters in, comma separated (defaults to the region of the environment)
+  #[arg(long, value_delimiter = ',', requires = "all_clusters", conflicts_with = "region")]
+  pub regions: Vec<String>,
+
+  /// The AWS accounts to analyze clusters in, comma separated, by assuming the role provided by `--role-name`
+  #[arg(long, value_delimiter = ',', requires = "role_name")]
+  pub accounts: Vec<String>,
+
+  /// The name of the IAM role assumed in each of the accounts provided by `--accounts`
+  #[arg(long, requires_all = ["all_clusters", "accounts"])]
+  pub role_name: Option<String>,
+
+  /// The AWS profiles to analyze clusters with, comma separated
+  #[arg(long, value_delimiter = ',', requires = "all_clusters")]
+  pub profiles: Vec<String>,
+
   #[arg(short, long, value_enum, default_value_t)]
   pub format: output::Format,
 
@@ -82,6 +126,99 @@ pub struct Analysis {
   /// Exclude recommendations from the output
   #[arg(long)]
   pub ignore_recommended: bool,
+
+  /// Number of days prior to the end of standard support to report the cluster version as approaching end of support
+  #[arg(long, default_value_t = 90)]
+  pub support_window: i64,
+
+  /// Path to a release data file, in the same format as the embedded `data.yaml`, to extend or override the
+  /// Kubernetes versions known to `eksup` (i.e. - to add a version newly supported by Amazon EKS)
+  #[arg(long)]
+  pub data_file: Option<String>,
+
+  /// Write the results into the cluster as `UpgradeReadinessReport` custom resources
+  #[arg(long)]
+  pub write_report: bool,
+
+  /// The namespace where the cluster-wide `UpgradeReadinessReport` is written
+  #[arg(long, default_value = "eksup")]
+  pub report_namespace: String,
+
+  /// Create Kubernetes events on the resources that have findings
+  #[arg(long)]
+  pub emit_events: bool,
+
+  /// Repeat the analysis on the interval provided, in seconds, instead of exiting (serve mode)
+  #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+  pub interval: Option<u64>,
+}
+
+/// Create an iCalendar (.ics) file of the upcoming end of support deadlines for Amazon EKS clusters
+#[derive(Args, Debug, Serialize, Deserialize)]
+pub struct Calendar {
+  /// The name of the cluster(s) to include (defaults to all clusters)
+  #[arg(short, long, alias = "cluster-name")]
+  pub cluster: Vec<String>,
+
+  /// Only include clusters whose name matches the regular expression
+  #[arg(long)]
+  pub name_filter: Option<String>,
+
+  /// Only include clusters with the given tag, in the form of `key=value` (may be repeated)
+  #[arg(long = "tag", value_parser = fleet::parse_tag)]
+  pub tags: Vec<(String, String)>,
+
+  /// The AWS region where the cluster(s) are provisioned
+  #[arg(short, long)]
+  pub region: Option<String>,
+
+  /// The AWS regions to include clusters from, comma separated (defaults to the region of the environment)
+  #[arg(long, value_delimiter = ',', conflicts_with = "region")]
+  pub regions: Vec<String>,
+
+  /// The AWS accounts to include clusters from, comma separated, by assuming the role provided by `--role-name`
+  #[arg(long, value_delimiter = ',', requires = "role_name")]
+  pub accounts: Vec<String>,
+
+  /// The name of the IAM role assumed in each of the accounts provided by `--accounts`
+  #[arg(long, requires = "accounts")]
+  pub role_name: Option<String>,
+
+  /// The AWS profiles to include clusters with, comma separated
+  #[arg(long, value_delimiter = ',')]
+  pub profiles: Vec<String>,
+
+  /// Number of days prior to each deadline to be reminded
+  #[arg(long, default_value_t = 30)]
+  pub reminder: u64,
+
+  /// Path to a release data file, in the same format as the embedded `data.yaml`, to extend or override the
+  /// Kubernetes versions known to `eksup` (i.e. - to add a version newly supported by Amazon EKS)
+  #[arg(long)]
+  pub data_file: Option<String>,
+
+  /// Write to file instead of stdout
+  #[arg(short, long)]
+  pub output: Option<String>,
+}
+
+/// Scan Kubernetes manifests for potential upgrade issues, without access to a cluster
+#[derive(Args, Debug, Serialize, Deserialize)]
+pub struct Scan {
+  /// Files or directories of Kubernetes manifests (YAML or JSON) to scan, or `-` to read from stdin
+  #[arg(required = true)]
+  pub paths: Vec<String>,
+
+  /// The Kubernetes version the manifests will be deployed to (i.e. - the version the cluster is upgraded to)
+  #[arg(short, long)]
+  pub target_version: String,
+
+  #[arg(short, long, value_enum, default_value_t)]
+  pub format: output::Format,
+
+  /// Write to file instead of stdout
+  #[arg(short, long)]
+  pub output: Option<String>,
 }
 
 /// Create artifacts using the analysis data
@@ -95,6 +232,10 @@ pub struct Create {
 pub enum CreateCommands {
   #[command(arg_required_else_help = true)]
   Playbook(Playbook),
+  #[command(arg_required_else_help = true)]
+  Manifests(Manifests),
+  #[command(arg_required_else_help = true)]
+  StorageClasses(StorageClasses),
 }
 
 /// Create a playbook for upgrading an Amazon EKS cluster
@@ -111,34 +252,226 @@ pub struct Playbook {
   /// Name of the playbook saved locally
   #[arg(short, long)]
   pub filename: Option<String>,
+
+  /// Number of days prior to the end of standard support to report the cluster version as approaching end of support
+  #[arg(long, default_value_t = 90)]
+  pub support_window: i64,
+
+  /// Path to a release data file, in the same format as the embedded `data.yaml`, to extend or override the
+  /// Kubernetes versions known to `eksup` (i.e. - to add a version newly supported by Amazon EKS)
+  #[arg(long)]
+  pub data_file: Option<String>,
   // /// Exclude recommendations from the output
   // #[arg(long)]
   // pub ignore_recommended: bool,
 }
 
+/// Create the Kubernetes manifests for running `eksup` on the cluster
+#[derive(Args, Debug, Serialize, Deserialize)]
+pub struct Manifests {
+  /// The name of the cluster to analyze
+  #[arg(short, long, alias = "cluster-name", value_enum)]
+  pub cluster: String,
+
+  /// The AWS region where the cluster is provisioned
+  #[arg(short, long)]
+  pub region: Option<String>,
+
+  /// The namespace where `eksup` will be deployed
+  #[arg(short, long, default_value = "eksup")]
+  pub namespace: String,
+
+  /// The cron schedule the analysis is run on
+  #[arg(short, long, default_value = "0 */12 * * *")]
+  pub schedule: String,
+
+  /// Run `eksup` as a Deployment that repeats the analysis on the interval provided, in seconds, instead of a CronJob
+  #[arg(long, value_parser = clap::value_parser!(u64).range(1..), conflicts_with = "schedule")]
+  pub interval: Option<u64>,
+
+  /// The container image used to run `eksup`
+  #[arg(short, long)]
+  pub image: String,
+
+  /// The IAM role ARN used by the service account (IAM roles for service accounts)
+  #[arg(long)]
+  pub role_arn: Option<String>,
+
+  /// Grant access to list Secrets across the cluster, required to check the Helm release history (K8S013)
+  #[arg(long)]
+  pub helm: bool,
+
+  /// Write to file instead of stdout
+  #[arg(short, long)]
+  pub output: Option<String>,
+}
+
+/// Create the `gp3` equivalents of the `gp2` StorageClasses on the cluster, provisioned by the Amazon EBS CSI driver
+#[derive(Args, Debug, Serialize, Deserialize)]
+pub struct StorageClasses {
+  /// The name of the cluster to analyze
+  #[arg(short, long, alias = "cluster-name", value_enum)]
+  pub cluster: String,
+
+  /// Write to file instead of stdout
+  #[arg(short, long)]
+  pub output: Option<String>,
+}
+
 /// Someting TODO
 pub async fn analyze(args: &Analysis) -> Result<()> {
-  let aws_config = get_config(&args.region.to_owned()).await?;
+  let Some(interval) = args.interval else {
+    return analyze_once(args).await;
+  };
+
+  // Serve mode - a failed analysis is reported and retried on the next interval instead of exiting
+  loop {
+    if let Err(err) = analyze_once(args).await {
+      warn!("Analysis failed, retrying in {interval} seconds: {err:?}");
+    }
+    tokio::time::sleep(Duration::from_secs(interval)).await;
+  }
+}
+
+/// Run the analysis once, of the cluster or of all clusters selected
+async fn analyze_once(args: &Analysis) -> Result<()> {
+  let catalog = version::Catalog::load(&args.data_file)?;
+  if args.all_clusters {
+    return analyze_fleet(args, &catalog).await;
+  }
+
+  let aws_config = get_config(&args.region, &None).await?;
   let eks_client = aws_sdk_eks::Client::new(&aws_config);
-  let cluster = eks::get_cluster(&eks_client, &args.cluster).await?;
+
+  let cluster_name = args.cluster.as_ref().context("Cluster name not provided")?;
+  let cluster = eks::get_cluster(&eks_client, cluster_name).await?;
+
+  let k8s_client = analysis::get_k8s_client(cluster_name).await?;
 
   // All checks and validations on input should happen above/before running the analysis
-  let results = analysis::analyze(&aws_config, &cluster).await?;
+  let results = analysis::analyze(&aws_config, &k8s_client, &cluster, &catalog, args.support_window).await?;
   output::output(&results, &args.format, &args.output).await?;
+  write_results(args, &k8s_client, &cluster, &results).await?;
+
+  Ok(())
+}
+
+/// Analyze all clusters across the profiles, accounts, and regions provided, reporting a summary for each cluster
+async fn analyze_fleet(args: &Analysis, catalog: &version::Catalog) -> Result<()> {
+  let selector = fleet::Selector {
+    name_filter: args.name_filter.as_deref().map(Regex::new).transpose()?,
+    tags: args.tags.to_owned(),
+    profiles: args.profiles.to_owned(),
+    accounts: args.accounts.to_owned(),
+    regions: match &args.region {
+      Some(region) => vec![region.to_owned()],
+      None => args.regions.to_owned(),
+    },
+    role_name: args.role_name.to_owned(),
+  };
+
+  let mut summaries = Vec::new();
+  for discovered in fleet::discover(&selector).await? {
+    // Failures are reported per account/region and per cluster to avoid one from halting the analysis of the others
+    let clusters = match discovered.clusters {
+      Ok(clusters) => clusters,
+      Err(err) => {
+        summaries.push(fleet::ClusterSummary::from_error(
+          &discovered.scope,
+          discovered.region,
+          err,
+        ));
+        continue;
+      }
+    };
+
+    for cluster in &clusters {
+      let results = analyze_cluster(args, catalog, &discovered.aws_config, cluster).await;
+      summaries.push(fleet::ClusterSummary::new(cluster, results));
+    }
+  }
+
+  let results = fleet::FleetResults { clusters: summaries };
+  output::output(&results, &args.format, &args.output).await?;
+
+  Ok(())
+}
+
+/// Analyze a cluster, as part of a multi-cluster analysis, using credentials derived from the AWS config
+async fn analyze_cluster(
+  args: &Analysis,
+  catalog: &version::Catalog,
+  aws_config: &aws_config::SdkConfig,
+  cluster: &aws_sdk_eks::types::Cluster,
+) -> Result<analysis::Results> {
+  let k8s_client = analysis::get_eks_k8s_client(aws_config, cluster).await?;
+
+  let results = analysis::analyze(aws_config, &k8s_client, cluster, catalog, args.support_window).await?;
+  write_results(args, &k8s_client, cluster, &results).await?;
+
+  Ok(results)
+}
+
+/// Write the results back into the cluster, when requested
+async fn write_results(
+  args: &Analysis,
+  k8s_client: &kube::Client,
+  cluster: &aws_sdk_eks::types::Cluster,
+  results: &analysis::Results,
+) -> Result<()> {
+  if !args.write_report && !args.emit_events {
+    return Ok(());
+  }
+
+  let cluster_version: version::Version = cluster.version().context("Cluster version not found")?.parse()?;
+  let entries = results.entries();
+
+  if args.write_report {
+    let spec = k8s::UpgradeReadinessReportSpec {
+      cluster_name: cluster.name().context("Cluster name not found")?.to_owned(),
+      current_version: cluster_version.to_string(),
+      target_version: cluster_version.next_minor().to_string(),
+    };
+    k8s::write_reports(k8s_client, &args.report_namespace, &spec, &entries).await?;
+  }
+
+  if args.emit_events {
+    k8s::emit_events(k8s_client, &entries).await?;
+  }
 
   Ok(())
 }
 
 /// Get the configuration to authn/authz with AWS that will be used across AWS clients
-async fn get_config(region: &Option<String>) -> Result<aws_config::SdkConfig> {
+///
+/// When a profile is provided, the credentials and region (when a region is not provided) of the profile are used
+async fn get_config(region: &Option<String>, profile: &Option<String>) -> Result<aws_config::SdkConfig> {
   let aws_region = match region {
     Some(region) => Some(Region::new(region.to_owned())),
     None => env::var("AWS_REGION").ok().map(Region::new),
   };
 
-  let region_provider = RegionProviderChain::first_try(aws_region).or_default_provider();
+  let mut region_provider = RegionProviderChain::first_try(aws_region);
+  if let Some(profile) = profile {
+    region_provider = region_provider.or_else(ProfileFileRegionProvider::builder().profile_name(profile).build());
+  }
 
-  Ok(aws_config::from_env().region(region_provider).load().await)
+  let mut loader = aws_config::from_env().region(region_provider.or_default_provider());
+  if let Some(profile) = profile {
+    loader = loader.profile_name(profile);
+  }
+
+  Ok(loader.load().await)
+}
+
+/// Create a calendar of the upcoming end of support deadlines for the clusters selected
+pub async fn calendar(args: &Calendar) -> Result<()> {
+  calendar::create(args).await
+}
+
+/// Scan Kubernetes manifests for potential upgrade issues, exiting with an error when remediation is required
+pub async fn scan(args: &Scan) -> Result<()> {
+  scan::scan(args).await
 }
 
 /// Someting TODO
@@ -146,26 +479,54 @@ pub async fn create(args: &Create) -> Result<()> {
   match &args.command {
     CreateCommands::Playbook(playbook) => {
       // Query Kubernetes first so that we can get AWS details that require them
-      let aws_config = get_config(&playbook.region.to_owned()).await?;
+      let aws_config = get_config(&playbook.region, &None).await?;
       let region = aws_config.region().unwrap().to_string();
 
       let eks_client = aws_sdk_eks::Client::new(&aws_config);
       let cluster = eks::get_cluster(&eks_client, &playbook.cluster).await?;
-      let cluster_version = cluster.version().context("Cluster version not found")?;
+      let cluster_version: version::Version = cluster.version().context("Cluster version not found")?.parse()?;
 
-      if version::LATEST.eq(cluster_version) {
-        println!("Cluster is already at the latest supported version: {cluster_version}");
-        println!("Nothing to upgrade at this time");
-        return Ok(());
+      let catalog = version::Catalog::load(&playbook.data_file)?;
+      let target_version = cluster_version.next_minor();
+      if !catalog.is_supported(&target_version) {
+        if catalog.latest() == Some(cluster_version) {
+          println!("Cluster is already at the latest supported version: {cluster_version}");
+          println!("Nothing to upgrade at this time");
+          return Ok(());
+        }
+        bail!("Version {target_version} is not known to eksup, its release data can be provided using `--data-file`");
       }
 
-      let results = analysis::analyze(&aws_config, &cluster).await?;
+      let k8s_client = analysis::get_k8s_client(&playbook.cluster).await?;
+      let results = analysis::analyze(&aws_config, &k8s_client, &cluster, &catalog, playbook.support_window).await?;
 
-      if let Err(err) = playbook::create(playbook, region, &cluster, results) {
+      if let Err(err) = playbook::create(playbook, region, &cluster, &catalog, results) {
         eprintln!("{err}");
         process::exit(2);
       }
     }
+    CreateCommands::Manifests(manifests) => {
+      // The access granted to query removed APIs is limited to the APIs served by the cluster version
+      let aws_config = get_config(&manifests.region, &None).await?;
+      let eks_client = aws_sdk_eks::Client::new(&aws_config);
+      let cluster = eks::get_cluster(&eks_client, &manifests.cluster).await?;
+      let cluster_version: version::Version = cluster.version().context("Cluster version not found")?.parse()?;
+
+      manifests::create(manifests, &cluster_version)?
+    }
+    CreateCommands::StorageClasses(storage_classes) => {
+      let k8s_client = analysis::get_k8s_client(&storage_classes.cluster).await?;
+      let rendered = k8s::get_gp3_manifests(&k8s_client).await?;
+      if rendered.is_empty() {
+        eprintln!("No StorageClasses were found that provision gp2 Amazon EBS volumes");
+        return Ok(());
+      }
+
+      match &storage_classes.output {
+        Some(filename) => fs::write(filename, rendered)?,
+        None => print!("{rendered}"),
+      }
+    }
   }
 
   Ok(())
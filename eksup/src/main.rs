@@ -4,7 +4,7 @@
 
 use anyhow::Result;
 use clap::Parser;
-use eksup::{analyze, create, Cli, Commands};
+use eksup::{analyze, calendar, create, scan, Cli, Commands};
 use tracing_log::AsTrace;
 use tracing_subscriber::FmtSubscriber;
 
@@ -21,6 +21,8 @@ async fn main() -> Result<()> {
   match &cli.commands {
     Commands::Analyze(args) => analyze(args).await?,
     Commands::Create(args) => create(args).await?,
+    Commands::Calendar(args) => calendar(args).await?,
+    Commands::Scan(args) => scan(args).await?,
   }
 
   Ok(())
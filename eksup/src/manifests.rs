@@ -0,0 +1,267 @@
+use std::{
+  collections::{BTreeMap, BTreeSet},
+  fs,
+};
+
+use anyhow::Result;
+use handlebars::{no_escape, Handlebars};
+use serde::{Deserialize, Serialize};
+
+use crate::{k8s, playbook::Templates, version, Manifests};
+
+/// Data to populate the template for rendering the in-cluster manifests
+///
+/// The manifests provide everything required to run `eksup` on the cluster
+/// it is analyzing: the `UpgradeReadinessReport` custom resource definition,
+/// a namespace, a service account (optionally annotated for IAM roles for
+/// service accounts), a cluster role and binding scoped to the resources
+/// inspected and the reports written, and a CronJob to run the analysis on a schedule
+/// (or a Deployment that repeats the analysis on an interval, when `--interval` is provided)
+#[derive(Debug, Serialize, Deserialize)]
+struct ManifestTemplateData {
+  report_crd: String,
+  namespace: String,
+  schedule: String,
+  /// Interval, in seconds, the analysis is repeated on by the Deployment (serve mode)
+  interval: Option<u64>,
+  image: String,
+  role_arn: Option<String>,
+  /// Grants access to list Secrets, used to read the Helm release history
+  helm: bool,
+  /// Grants access to list the objects served by the APIs that may be queried for removed API usage
+  removed_api_rules: Vec<RbacRule>,
+  /// Arguments passed to the `eksup` container
+  args: Vec<String>,
+}
+
+/// A rule of the ClusterRole, granting `list` on the resources of an API group
+#[derive(Debug, Serialize, Deserialize)]
+struct RbacRule {
+  group: String,
+  resources: BTreeSet<String>,
+}
+
+/// Returns the rules required to list the objects served by the APIs removed after the cluster version
+///
+/// The analysis only queries the removed APIs that the cluster still serves, so the access granted is limited
+/// to those; the manifests should be re-created after the cluster is upgraded to drop the access no longer needed
+fn get_removed_api_rules(cluster_version: &version::Version) -> Result<Vec<RbacRule>> {
+  let mut resources: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+  for api in k8s::get_apis_removed_after(cluster_version)? {
+    let (group, _) = k8s::split_api_version(&api.api_version);
+    resources.entry(group.to_owned()).or_default().insert(api.resource);
+  }
+
+  Ok(
+    resources
+      .into_iter()
+      .map(|(group, resources)| RbacRule { group, resources })
+      .collect(),
+  )
+}
+
+/// Arguments passed to `eksup` when running on the cluster
+fn get_container_args(args: &Manifests) -> Vec<String> {
+  let mut container_args = vec![
+    "analyze".to_string(),
+    "--cluster".to_string(),
+    args.cluster.to_owned(),
+    "--format".to_string(),
+    "json".to_string(),
+    "--write-report".to_string(),
+    "--report-namespace".to_string(),
+    args.namespace.to_owned(),
+  ];
+
+  if let Some(region) = &args.region {
+    container_args.push("--region".to_string());
+    container_args.push(region.to_owned());
+  }
+
+  if let Some(interval) = args.interval {
+    container_args.push("--interval".to_string());
+    container_args.push(interval.to_string());
+  }
+
+  container_args
+}
+
+/// Render the manifests for running `eksup` on the cluster of the version provided
+fn render(args: &Manifests, cluster_version: &version::Version) -> Result<String> {
+  let mut handlebars = Handlebars::new();
+  // Output is YAML, not HTML - nothing should be escaped
+  handlebars.register_escape_fn(no_escape);
+  handlebars.register_embed_templates::<Templates>()?;
+
+  let tmpl_data = ManifestTemplateData {
+    report_crd: k8s::get_report_crd()?,
+    namespace: args.namespace.to_owned(),
+    schedule: args.schedule.to_owned(),
+    interval: args.interval,
+    image: args.image.to_owned(),
+    role_arn: args.role_arn.to_owned(),
+    helm: args.helm,
+    removed_api_rules: get_removed_api_rules(cluster_version)?,
+    args: get_container_args(args),
+  };
+
+  Ok(handlebars.render("manifests.yaml", &tmpl_data)?)
+}
+
+/// Create the manifests for running `eksup` on the cluster of the version provided
+pub(crate) fn create(args: &Manifests, cluster_version: &version::Version) -> Result<()> {
+  let rendered = render(args, cluster_version)?;
+
+  match &args.output {
+    Some(filename) => fs::write(filename, rendered)?,
+    None => print!("{rendered}"),
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const VERSION: version::Version = version::Version::new(1, 24);
+
+  fn args(role_arn: Option<&str>, helm: bool) -> Manifests {
+    Manifests {
+      cluster: "example".to_string(),
+      region: Some("us-east-1".to_string()),
+      namespace: "eksup".to_string(),
+      schedule: "0 */12 * * *".to_string(),
+      interval: None,
+      image: "public.ecr.aws/example/eksup:latest".to_string(),
+      role_arn: role_arn.map(String::from),
+      helm,
+      output: None,
+    }
+  }
+
+  /// Parses each document of the rendered manifests
+  fn parse(rendered: &str) -> Vec<serde_yaml::Value> {
+    serde_yaml::Deserializer::from_str(rendered)
+      .map(|document| serde_yaml::Value::deserialize(document).unwrap())
+      .filter(|document| !document.is_null())
+      .collect()
+  }
+
+  fn find<'a>(documents: &'a [serde_yaml::Value], kind: &str) -> &'a serde_yaml::Value {
+    documents.iter().find(|document| document["kind"] == kind).unwrap()
+  }
+
+  #[test]
+  fn can_render_manifests() {
+    let documents = parse(&render(&args(None, false), &VERSION).unwrap());
+    let kinds: Vec<&str> = documents.iter().filter_map(|d| d["kind"].as_str()).collect();
+    assert_eq!(
+      kinds,
+      vec![
+        "CustomResourceDefinition",
+        "Namespace",
+        "ServiceAccount",
+        "ClusterRole",
+        "ClusterRoleBinding",
+        "CronJob"
+      ]
+    );
+
+    let service_account = find(&documents, "ServiceAccount");
+    assert!(service_account["metadata"]["annotations"].is_null());
+
+    let container = &find(&documents, "CronJob")["spec"]["jobTemplate"]["spec"]["template"]["spec"]["containers"][0];
+    let container_args: Vec<&str> = container["args"]
+      .as_sequence()
+      .unwrap()
+      .iter()
+      .filter_map(|a| a.as_str())
+      .collect();
+    assert_eq!(container_args, get_container_args(&args(None, false)));
+    assert!(container_args.contains(&"--region"));
+  }
+
+  #[test]
+  fn can_render_role_arn() {
+    let role_arn = "arn:aws:iam::111111111111:role/eksup";
+    let documents = parse(&render(&args(Some(role_arn), false), &VERSION).unwrap());
+
+    let service_account = find(&documents, "ServiceAccount");
+    assert_eq!(
+      service_account["metadata"]["annotations"]["eks.amazonaws.com/role-arn"],
+      role_arn
+    );
+  }
+
+  #[test]
+  fn can_render_helm_rule() {
+    let has_secrets_rule = |helm: bool| {
+      let documents = parse(&render(&args(None, helm), &VERSION).unwrap());
+      find(&documents, "ClusterRole")["rules"]
+        .as_sequence()
+        .unwrap()
+        .iter()
+        .flat_map(|rule| rule["resources"].as_sequence().into_iter().flatten())
+        .any(|resource| resource == "secrets")
+    };
+
+    assert!(!has_secrets_rule(false));
+    assert!(has_secrets_rule(true));
+  }
+
+  #[test]
+  fn can_render_removed_api_rules() {
+    let rules = |cluster_version: version::Version| -> BTreeMap<String, Vec<String>> {
+      get_removed_api_rules(&cluster_version)
+        .unwrap()
+        .into_iter()
+        .map(|rule| (rule.group, rule.resources.into_iter().collect()))
+        .collect()
+    };
+
+    // APIs removed at or before the cluster version are no longer served, and therefore not granted
+    let granted = rules(VERSION);
+    assert!(!granted.contains_key("rbac.authorization.k8s.io"));
+    assert!(!granted.contains_key("apps"));
+    // Each group is only granted its own removed resources
+    assert_eq!(granted["node.k8s.io"], vec!["runtimeclasses"]);
+    assert_eq!(granted["batch"], vec!["cronjobs"]);
+
+    let granted = rules(version::Version::new(1, 21));
+    assert_eq!(
+      granted["rbac.authorization.k8s.io"],
+      vec!["clusterrolebindings", "clusterroles", "rolebindings", "roles"]
+    );
+
+    let documents = parse(&render(&args(None, false), &VERSION).unwrap());
+    let cluster_role = serde_yaml::to_string(find(&documents, "ClusterRole")).unwrap();
+    assert!(cluster_role.contains("runtimeclasses"));
+    assert!(!cluster_role.contains("rolebindings"));
+  }
+
+  #[test]
+  fn can_render_deployment() {
+    let args = Manifests {
+      interval: Some(3600),
+      ..args(None, false)
+    };
+    let documents = parse(&render(&args, &VERSION).unwrap());
+    assert!(documents.iter().all(|document| document["kind"] != "CronJob"));
+
+    let deployment = find(&documents, "Deployment");
+    assert_eq!(deployment["spec"]["replicas"], 1);
+    assert_eq!(
+      deployment["spec"]["selector"]["matchLabels"],
+      deployment["spec"]["template"]["metadata"]["labels"]
+    );
+
+    let container_args: Vec<&str> = deployment["spec"]["template"]["spec"]["containers"][0]["args"]
+      .as_sequence()
+      .unwrap()
+      .iter()
+      .filter_map(|a| a.as_str())
+      .collect();
+    assert!(container_args.ends_with(&["--interval", "3600"]));
+  }
+}
@@ -4,28 +4,26 @@ use anyhow::Result;
 use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
 
-use crate::analysis;
-
 /// Converts vec into comma separated string for tabled output
 pub fn tabled_vec_to_string(v: &[String]) -> String {
   v.join(", ")
 }
 
-#[derive(Clone, Copy, Debug, ValueEnum, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, Default, ValueEnum, Serialize, Deserialize)]
 pub enum Format {
   /// JSON format used for logging or writing to a *.json file
   Json,
   /// Text format used for writing to stdout
+  #[default]
   Text,
 }
 
-impl Default for Format {
-  fn default() -> Self {
-    Self::Text
-  }
+/// Results that can be written in any of the supported output formats
+pub(crate) trait Output: Serialize {
+  fn to_stdout_table(&self) -> Result<String>;
 }
 
-pub(crate) async fn output(results: &analysis::Results, format: &Format, filename: &Option<String>) -> Result<()> {
+pub(crate) async fn output<T: Output>(results: &T, format: &Format, filename: &Option<String>) -> Result<()> {
   let output = match format {
     Format::Json => serde_json::to_string(&results)?,
     Format::Text => results.to_stdout_table()?,
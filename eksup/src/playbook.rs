@@ -1,6 +1,6 @@
-use std::{collections::HashMap, fs};
+use std::fs;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use aws_sdk_eks::types::Cluster;
 use handlebars::Handlebars;
 use rust_embed::RustEmbed;
@@ -15,20 +15,7 @@ use crate::{analysis, eks, finding::Findings, version, Playbook};
 /// embedded into the binary for distribution
 #[derive(RustEmbed)]
 #[folder = "templates/"]
-struct Templates;
-
-/// Relevant data for a Kubernetes release
-///
-/// Used to populate the playbook templates with the data associated
-/// to a specific Kubernetes release version
-#[derive(Debug, Serialize, Deserialize)]
-struct Release {
-  release_url: String,
-  deprecation_url: Option<String>,
-}
-
-/// Type alias for Kubernetes version string (i.e. - "1.21")
-type Version = String;
+pub(crate) struct Templates;
 
 /// Data to populate the template(s) for rendering the upgrade playbook
 ///
@@ -47,6 +34,9 @@ pub struct TemplateData {
   control_plane_ips: String,
   pod_ips: String,
   cluster_health: String,
+  api_services: String,
+  node_health: String,
+  end_of_support: String,
   addon_health: String,
   addon_version_compatibility: String,
   data_plane_findings: eks::DataPlaneFindings,
@@ -57,20 +47,23 @@ pub struct TemplateData {
   // kubernetes_findings: k8s::KubernetesFindings,
   min_replicas: String,
   min_ready_seconds: String,
+  pod_disruption_budget: String,
+  blocking_pod_disruption_budgets: String,
   pod_topology_distribution: String,
-  readiness_probe: String,
+  pod_placement: String,
+  bare_pods: String,
+  eviction_blockers: String,
+  probes: String,
   termination_grace_period: String,
   docker_socket: String,
   pod_security_policy: String,
+  admission_webhooks: String,
+  ebs_csi_migration: String,
+  gp2_volumes: String,
   kube_proxy_version_skew: String,
-}
-
-fn get_release_data() -> Result<HashMap<Version, Release>> {
-  let data_file = Templates::get("data.yaml").unwrap();
-  let contents = std::str::from_utf8(data_file.data.as_ref())?;
-  let data: HashMap<Version, Release> = serde_yaml::from_str(contents)?;
-
-  Ok(data)
+  removed_apis: String,
+  helm_releases: String,
+  custom_resources: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -109,17 +102,24 @@ fn char_replace(text: String) -> String {
     .replace("&#x3D;", "=")
 }
 
-pub(crate) fn create(args: &Playbook, region: String, cluster: &Cluster, analysis: analysis::Results) -> Result<()> {
+pub(crate) fn create(
+  args: &Playbook,
+  region: String,
+  cluster: &Cluster,
+  catalog: &version::Catalog,
+  analysis: analysis::Results,
+) -> Result<()> {
   let mut handlebars = Handlebars::new();
   handlebars.register_embed_templates::<Templates>()?;
 
   let cluster_name = cluster.name().unwrap();
-  let cluster_version = cluster.version().unwrap();
-  let target_version = version::get_target_version(cluster_version)?;
+  let cluster_version: version::Version = cluster.version().context("Cluster version not found")?.parse()?;
+  let target_version = cluster_version.next_minor();
   let default_playbook_name = format!("{cluster_name}_v{target_version}_upgrade.md");
 
-  let release_data = get_release_data()?;
-  let release = release_data.get(&target_version).unwrap();
+  let release = catalog
+    .get(&target_version)
+    .with_context(|| format!("Release data not found for {target_version}, it can be provided using `--data-file`"))?;
 
   let cluster_findings = analysis.cluster;
   let data_plane_findings = analysis.data_plane;
@@ -131,7 +131,7 @@ pub(crate) fn create(args: &Playbook, region: String, cluster: &Cluster, analysi
   let eks_mng_tmpl_data = EksManagedNodeGroupTemplateData {
     region: region.to_owned(),
     cluster_name: cluster_name.to_owned(),
-    target_version: target_version.to_owned(),
+    target_version: target_version.to_string(),
     eks_managed_nodegroup_health: data_plane_findings
       .eks_managed_nodegroup_health
       .to_markdown_table("\t")?,
@@ -144,7 +144,7 @@ pub(crate) fn create(args: &Playbook, region: String, cluster: &Cluster, analysi
   let self_mng_tmpl_data = SelfManagedNodeGroupTemplateData {
     region: region.to_owned(),
     cluster_name: cluster_name.to_owned(),
-    target_version: target_version.to_owned(),
+    target_version: target_version.to_string(),
     self_managed_nodegroup_update: data_plane_findings
       .self_managed_nodegroup_update
       .to_markdown_table("\t")?,
@@ -155,15 +155,15 @@ pub(crate) fn create(args: &Playbook, region: String, cluster: &Cluster, analysi
   let fargate_tmpl_data = FargateProfileTemplateData {
     region: region.to_owned(),
     cluster_name: cluster_name.to_owned(),
-    target_version: target_version.to_owned(),
+    target_version: target_version.to_string(),
   };
   let fargate_profile_template = char_replace(handlebars.render("fargate-node.md", &fargate_tmpl_data)?);
 
   let tmpl_data = TemplateData {
     region,
     cluster_name: cluster_name.to_owned(),
-    current_version: cluster_version.to_owned(),
-    target_version,
+    current_version: cluster_version.to_string(),
+    target_version: target_version.to_string(),
     k8s_release_url: release.release_url.to_string(),
     k8s_deprecation_url: match &release.deprecation_url {
       Some(url) => url.to_string(),
@@ -172,6 +172,9 @@ pub(crate) fn create(args: &Playbook, region: String, cluster: &Cluster, analysi
     control_plane_ips: subnet_findings.control_plane_ips.to_markdown_table("\t")?,
     pod_ips: subnet_findings.pod_ips.to_markdown_table("\t")?,
     cluster_health: cluster_findings.cluster_health.to_markdown_table("\t")?,
+    api_services: kubernetes_findings.api_services.to_markdown_table("\t")?,
+    node_health: kubernetes_findings.node_health.to_markdown_table("\t")?,
+    end_of_support: cluster_findings.end_of_support.to_markdown_table("\t")?,
     addon_health: addon_findings.health.to_markdown_table("\t")?,
     addon_version_compatibility: addon_findings.version_compatibility.to_markdown_table("\t")?,
     data_plane_findings,
@@ -182,12 +185,25 @@ pub(crate) fn create(args: &Playbook, region: String, cluster: &Cluster, analysi
     version_skew: kubernetes_findings.version_skew.to_markdown_table("\t")?,
     min_replicas: kubernetes_findings.min_replicas.to_markdown_table("\t")?,
     min_ready_seconds: kubernetes_findings.min_ready_seconds.to_markdown_table("\t")?,
+    pod_disruption_budget: kubernetes_findings.pod_disruption_budget.to_markdown_table("\t")?,
+    blocking_pod_disruption_budgets: kubernetes_findings
+      .blocking_pod_disruption_budgets
+      .to_markdown_table("\t")?,
     pod_topology_distribution: kubernetes_findings.pod_topology_distribution.to_markdown_table("\t")?,
-    readiness_probe: kubernetes_findings.readiness_probe.to_markdown_table("\t")?,
+    pod_placement: kubernetes_findings.pod_placement.to_markdown_table("\t")?,
+    bare_pods: kubernetes_findings.bare_pods.to_markdown_table("\t")?,
+    eviction_blockers: kubernetes_findings.eviction_blockers.to_markdown_table("\t")?,
+    probes: kubernetes_findings.probes.to_markdown_table("\t")?,
     termination_grace_period: kubernetes_findings.termination_grace_period.to_markdown_table("\t")?,
     docker_socket: kubernetes_findings.docker_socket.to_markdown_table("\t")?,
     pod_security_policy: kubernetes_findings.pod_security_policy.to_markdown_table("\t")?,
+    admission_webhooks: kubernetes_findings.admission_webhooks.to_markdown_table("\t")?,
+    ebs_csi_migration: kubernetes_findings.ebs_csi_migration.to_markdown_table("\t")?,
+    gp2_volumes: kubernetes_findings.gp2_volumes.to_markdown_table("\t")?,
     kube_proxy_version_skew: kubernetes_findings.kube_proxy_version_skew.to_markdown_table("\t")?,
+    removed_apis: kubernetes_findings.removed_apis.to_markdown_table("\t")?,
+    helm_releases: kubernetes_findings.helm_releases.to_markdown_table("\t")?,
+    custom_resources: kubernetes_findings.custom_resources.to_markdown_table("\t")?,
   };
 
   let filename = match &args.filename {
@@ -0,0 +1,186 @@
+use std::{
+  fs,
+  io::{self, Read},
+  path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{
+  finding::{self, Findings},
+  k8s, output, version, Scan,
+};
+
+/// File extensions of the manifests read from directories
+const EXTENSIONS: [&str; 3] = ["json", "yaml", "yml"];
+
+impl output::Output for k8s::ManifestFindings {
+  fn to_stdout_table(&self) -> Result<String> {
+    let mut output = String::new();
+
+    output.push_str(&self.min_replicas.to_stdout_table()?);
+    output.push_str(&self.min_ready_seconds.to_stdout_table()?);
+    output.push_str(&self.pod_topology_distribution.to_stdout_table()?);
+    output.push_str(&self.probes.to_stdout_table()?);
+    output.push_str(&self.termination_grace_period.to_stdout_table()?);
+    output.push_str(&self.docker_socket.to_stdout_table()?);
+    output.push_str(&self.removed_apis.to_stdout_table()?);
+
+    Ok(output)
+  }
+}
+
+/// Returns the manifest files within the path, recursively, in a stable order
+fn get_files(path: &Path) -> Result<Vec<PathBuf>> {
+  if !path.is_dir() {
+    return Ok(vec![path.to_owned()]);
+  }
+
+  let mut files = Vec::new();
+  for entry in fs::read_dir(path).with_context(|| format!("Unable to read {}", path.display()))? {
+    let path = entry?.path();
+    if path.is_dir() {
+      files.extend(get_files(&path)?);
+    } else if path
+      .extension()
+      .and_then(|ext| ext.to_str())
+      .map(|ext| EXTENSIONS.contains(&ext))
+      .unwrap_or(false)
+    {
+      files.push(path);
+    }
+  }
+  files.sort();
+
+  Ok(files)
+}
+
+/// Read the manifests from the paths provided, where `-` reads from stdin
+fn read_manifests(paths: &[String]) -> Result<Vec<k8s::Manifest>> {
+  let mut manifests = Vec::new();
+
+  for path in paths {
+    if path == "-" {
+      let mut contents = String::new();
+      io::stdin().read_to_string(&mut contents)?;
+      manifests.extend(k8s::Manifest::parse(path, &contents)?);
+      continue;
+    }
+
+    for file in get_files(Path::new(path))? {
+      let contents = fs::read_to_string(&file).with_context(|| format!("Unable to read {}", file.display()))?;
+      manifests.extend(k8s::Manifest::parse(&file.display().to_string(), &contents)?);
+    }
+  }
+
+  Ok(manifests)
+}
+
+/// Scan the manifests for potential upgrade issues, failing when any finding requires remediation
+pub(crate) async fn scan(args: &Scan) -> Result<()> {
+  let target_version: version::Version = args.target_version.parse()?;
+  let manifests = read_manifests(&args.paths)?;
+
+  let findings = k8s::get_manifest_findings(&manifests, &target_version)?;
+  output::output(&findings, &args.format, &args.output).await?;
+
+  let summary = finding::Summary::new(&findings.entries());
+  if summary.required > 0 {
+    bail!(
+      "{} finding(s) require remediation prior to upgrading to {target_version}",
+      summary.required
+    );
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn can_read_manifests() {
+    let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/../tests");
+    let manifests = read_manifests(&[fixtures.to_string()]).unwrap();
+    let findings = k8s::get_manifest_findings(&manifests, &version::Version::new(1, 25)).unwrap();
+
+    assert!(manifests.iter().any(|m| m.kind() == "Namespace"));
+    assert!(findings.min_replicas.iter().any(|f| f.resource.name == "bad-dpl"));
+    assert!(findings.docker_socket.iter().any(|f| f.resource.name == "bad-dpl"));
+    assert!(!findings.probes.iter().any(|f| f.resource.name == "good-dpl"));
+    assert!(findings.removed_apis.is_empty());
+  }
+
+  #[test]
+  fn can_scan_apis_removed_in_1_16() {
+    let contents = r#"
+apiVersion: apps/v1beta2
+kind: Deployment
+metadata:
+  name: example
+  namespace: default
+spec:
+  replicas: 1
+  selector:
+    matchLabels:
+      app: example
+  template:
+    metadata:
+      labels:
+        app: example
+    spec:
+      containers:
+        - name: example
+          image: nginx
+"#;
+    let manifests = k8s::Manifest::parse("-", contents).unwrap();
+    let findings = k8s::get_manifest_findings(&manifests, &version::Version::new(1, 25)).unwrap();
+
+    let removed: Vec<(&str, &str, Option<&str>)> = findings
+      .removed_apis
+      .iter()
+      .map(|f| (f.api_version.as_str(), f.kind.as_str(), f.replacement.as_deref()))
+      .collect();
+    assert_eq!(removed, vec![("apps/v1beta2", "Deployment", Some("apps/v1"))]);
+    // The workload is still inspected as though it were written against `apps/v1`
+    assert!(findings.min_replicas.iter().any(|f| f.resource.name == "example"));
+  }
+
+  #[test]
+  fn can_scan_removed_apis() {
+    let contents = r#"
+apiVersion: v1
+kind: List
+items:
+  - apiVersion: policy/v1beta1
+    kind: PodDisruptionBudget
+    metadata:
+      name: example
+      namespace: default
+  - apiVersion: networking.k8s.io/v1beta1
+    kind: Ingress
+    metadata:
+      name: example
+      namespace: default
+---
+---
+{"apiVersion": "autoscaling/v2beta2", "kind": "HorizontalPodAutoscaler", "metadata": {"name": "example"}}
+"#;
+    let manifests = k8s::Manifest::parse("-", contents).unwrap();
+    assert_eq!(manifests.len(), 3);
+
+    let findings = k8s::get_manifest_findings(&manifests, &version::Version::new(1, 25)).unwrap();
+    let removed: Vec<(&str, &str)> = findings
+      .removed_apis
+      .iter()
+      .map(|f| (f.api_version.as_str(), f.source.as_str()))
+      .collect();
+
+    // `autoscaling/v2beta2` is not removed until 1.26
+    assert_eq!(
+      removed,
+      vec![("policy/v1beta1", "-"), ("networking.k8s.io/v1beta1", "-")]
+    );
+  }
+}
@@ -1,82 +1,226 @@
-use std::fmt;
+use std::{collections::HashMap, fmt, fs, str::FromStr};
 
-use anyhow::Result;
-use clap::ValueEnum;
-use seq_macro::seq;
+use anyhow::{Context, Result};
+use k8s_openapi::chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
-/// Latest support version
-pub const LATEST: &str = "1.26";
+use crate::playbook::Templates;
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Versions {
-  pub current: String,
-  pub target: String,
+/// Error returned when a Kubernetes version cannot be parsed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VersionError {
+  /// No version was found in the input (i.e. - an image without a tag)
+  Missing(String),
+  /// The input does not contain a valid `<major>.<minor>` version
+  Invalid(String),
 }
 
-seq!(N in 20..=26 {
-    /// Kubernetes version(s) supported
-    #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
-    pub enum KubernetesVersion {
-        #( V~N, )*
+impl fmt::Display for VersionError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      VersionError::Missing(input) => write!(f, "No Kubernetes version found in `{input}`"),
+      VersionError::Invalid(input) => write!(f, "Invalid Kubernetes version `{input}`"),
     }
+  }
+}
+
+impl std::error::Error for VersionError {}
+
+/// Kubernetes version, limited to the major and minor components that are relevant to upgrades
+///
+/// Parses the formats used by Amazon EKS (`1.20`, `v1.20.7-eks-123456`), upstream Kubernetes (`v1.22.7`),
+/// and container image tags (`602401143452.dkr.ecr.us-east-1.amazonaws.com/eks/kube-proxy:v1.26.2-minimal-eksbuild.1`).
+/// Versions are displayed and serialized in the form of `1.20`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Version {
+  pub major: u32,
+  pub minor: u32,
+}
+
+impl Version {
+  pub const fn new(major: u32, minor: u32) -> Self {
+    Version { major, minor }
+  }
+
+  /// Returns the next minor version
+  pub fn next_minor(&self) -> Self {
+    Version::new(self.major, self.minor + 1)
+  }
 
-    /// Formats the Kubernetes version as a string in the form of "1.X"
-    impl fmt::Display for KubernetesVersion {
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            match *self {
-                #( KubernetesVersion::V~N => write!(f, "1.{}", N), )*
-            }
-        }
+  /// Returns the number of minor versions `self` is ahead of `other` (negative when behind)
+  pub fn minor_skew(&self, other: &Version) -> i64 {
+    i64::from(self.minor) - i64::from(other.minor)
+  }
+
+  /// Returns the number of minor versions the kubelet may be older than the API server of this version
+  ///
+  /// Starting with Kubernetes 1.28, the kubelet may be up to three minor versions older than the
+  /// API server (n-3); prior to 1.28 it is limited to two minor versions (n-2)
+  /// <https://kubernetes.io/releases/version-skew-policy/#kubelet>
+  pub fn max_kubelet_skew(&self) -> i64 {
+    if *self >= Version::new(1, 28) {
+      3
+    } else {
+      2
     }
+  }
+}
+
+impl FromStr for Version {
+  type Err = VersionError;
 
-    /// Used by clap for acceptable values and converting from input to enum
-    impl ValueEnum for KubernetesVersion {
-        fn value_variants<'a>() -> &'a [Self] {
-            &[
-                #( Self::V~N, )*
-            ]
-        }
-
-        fn to_possible_value<'a>(&self) -> Option<clap::builder::PossibleValue> {
-            match self {
-                #( Self::V~N => Some(clap::builder::PossibleValue::new(format!("1.{}", N))), )*
-            }
-        }
+  fn from_str(input: &str) -> Result<Self, Self::Err> {
+    let trimmed = input.trim();
+    // Image references - drop the digest and take the tag (the registry may contain a port, hence the last `:`)
+    let version = match trimmed.split('@').next().unwrap_or_default() {
+      image if image.contains('/') || image.contains(':') => match image.rsplit_once(':') {
+        Some((repository, tag)) if !tag.contains('/') && !repository.is_empty() => tag,
+        _ => return Err(VersionError::Missing(input.to_owned())),
+      },
+      version => version,
+    };
+
+    let mut parts = version.trim_start_matches('v').split('.');
+    let mut component = || -> Option<u32> {
+      let digits: String = parts.next()?.chars().take_while(|c| c.is_ascii_digit()).collect();
+      digits.parse().ok()
+    };
+
+    match (component(), component()) {
+      (Some(major), Some(minor)) => Ok(Version::new(major, minor)),
+      _ => Err(VersionError::Invalid(input.to_owned())),
     }
-});
+  }
+}
 
-/// Get the Kubernetes version the cluster is intended to be upgraded to
-///
-/// Given the current Kubernetes version and the default behavior based on Kubernetes
-/// upgrade restrictions of one minor version upgrade at a time, return the
-/// next minor Kubernetes version
-/// TODO: This will change in the future when the strategy allows for `BlueGreen` upgrades
-pub(crate) fn get_target_version(current_version: &str) -> Result<String> {
-  let current_minor_version = current_version.split('.').collect::<Vec<&str>>()[1].parse::<i32>()?;
-
-  Ok(format!("1.{}", current_minor_version + 1))
+impl TryFrom<String> for Version {
+  type Error = VersionError;
+
+  fn try_from(value: String) -> Result<Self, Self::Error> {
+    value.parse()
+  }
+}
+
+impl From<Version> for String {
+  fn from(version: Version) -> Self {
+    version.to_string()
+  }
+}
+
+impl fmt::Display for Version {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}.{}", self.major, self.minor)
+  }
 }
 
-/// Given a version, parse the minor version
+/// Relevant data for a Kubernetes release
 ///
-/// For example, the format Amazon EKS of v1.20.7-eks-123456 returns 20
-/// Or the format of v1.22.7 returns 22
-pub(crate) fn parse_minor(version: &str) -> Result<i32> {
-  let version = version.split('.').collect::<Vec<&str>>();
-  let minor = version[1].parse::<i32>()?;
+/// Used to populate the playbook templates with the data associated to a specific
+/// Kubernetes release version, and to determine the support status of a version on Amazon EKS
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct Release {
+  pub release_url: String,
+  pub deprecation_url: Option<String>,
+  /// Date the version was released on Amazon EKS
+  pub eks_release: Option<NaiveDate>,
+  /// Date the version reaches the end of standard support on Amazon EKS
+  pub end_of_standard_support: Option<NaiveDate>,
+  /// Date the version reaches the end of extended support on Amazon EKS
+  ///
+  /// Versions that reached the end of standard support prior to the introduction
+  /// of extended support do not have an end of extended support date
+  pub end_of_extended_support: Option<NaiveDate>,
+}
 
-  Ok(minor)
+/// Support status of a version on Amazon EKS as of a given date
+#[derive(Debug, PartialEq)]
+pub(crate) enum Support {
+  /// Version is in standard support, with the number of days remaining
+  Standard(i64),
+  /// Version is in extended support, with the number of days remaining
+  Extended(i64),
+  /// Version is no longer supported, with the number of days since the end of support
+  Ended(i64),
+  /// Support dates for the version are not known
+  Unknown,
 }
 
-/// Given a version, normalize to a consistent format
+impl Release {
+  /// Returns the support status of the release as of the date provided
+  pub(crate) fn support(&self, date: NaiveDate) -> Support {
+    let end_of_standard_support = match self.end_of_standard_support {
+      Some(end) => end,
+      None => return Support::Unknown,
+    };
+
+    if date < end_of_standard_support {
+      return Support::Standard((end_of_standard_support - date).num_days());
+    }
+
+    match self.end_of_extended_support {
+      Some(end) if date < end => Support::Extended((end - date).num_days()),
+      Some(end) => Support::Ended((date - end).num_days()),
+      None => Support::Ended((date - end_of_standard_support).num_days()),
+    }
+  }
+}
+
+/// Catalog of the Kubernetes versions known to `eksup`, keyed by version
 ///
-/// For example, the format Amazon EKS uses is v1.20.7-eks-123456 which is normalized to 1.20
-pub(crate) fn normalize(version: &str) -> Result<String> {
-  let version = version.split('.').collect::<Vec<&str>>();
-  let normalized = format!("{}.{}", version[0].replace('v', ""), version[1]);
+/// The catalog is loaded from the `data.yaml` embedded in the binary, and can be extended or
+/// overridden with a user provided data file of the same format. This allows users to upgrade
+/// to versions newly supported by Amazon EKS without waiting for a new release of `eksup`
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Catalog {
+  releases: HashMap<Version, Release>,
+}
 
-  Ok(normalized)
+impl Catalog {
+  /// Load the catalog from the embedded data, merging in the releases from the data file provided (if any)
+  ///
+  /// Releases in the data file take precedence over the embedded releases of the same version
+  pub(crate) fn load(data_file: &Option<String>) -> Result<Self> {
+    let embedded = Templates::get("data.yaml").context("Release data not found")?;
+    let mut catalog = Self::from_yaml(std::str::from_utf8(embedded.data.as_ref())?)?;
+
+    if let Some(data_file) = data_file {
+      let contents = fs::read_to_string(data_file).with_context(|| format!("Unable to read {data_file}"))?;
+      let overrides = Self::from_yaml(&contents).with_context(|| format!("Unable to parse {data_file}"))?;
+      catalog.releases.extend(overrides.releases);
+    }
+
+    Ok(catalog)
+  }
+
+  fn from_yaml(contents: &str) -> Result<Self> {
+    let releases: HashMap<Version, Release> = serde_yaml::from_str(contents)?;
+
+    Ok(Catalog { releases })
+  }
+
+  /// Returns the release data of the version provided
+  pub(crate) fn get(&self, version: &Version) -> Option<&Release> {
+    self.releases.get(version)
+  }
+
+  /// Returns true if the version has been released on Amazon EKS
+  pub(crate) fn is_supported(&self, version: &Version) -> bool {
+    self
+      .get(version)
+      .map(|release| release.eks_release.is_some())
+      .unwrap_or(false)
+  }
+
+  /// Returns the latest version released on Amazon EKS
+  pub(crate) fn latest(&self) -> Option<Version> {
+    self
+      .releases
+      .iter()
+      .filter(|(_, release)| release.eks_release.is_some())
+      .map(|(version, _)| *version)
+      .max()
+  }
 }
 
 #[cfg(test)]
@@ -84,28 +228,119 @@ mod tests {
   use super::*;
 
   #[test]
-  fn can_parse_minor() {
+  fn can_parse_version() {
     let input_expected = vec![
-      ("v1.20.7-eks-123456", 20),
-      // TODO - add more test cases and failure cases
+      ("1.20", Version::new(1, 20)),
+      ("v1.20.7-eks-123456", Version::new(1, 20)),
+      ("v1.22.7", Version::new(1, 22)),
+      ("1.26+", Version::new(1, 26)),
+      ("kube-proxy:v1.26.2-minimal-eksbuild.1", Version::new(1, 26)),
+      (
+        "602401143452.dkr.ecr.us-east-1.amazonaws.com/eks/kube-proxy:v1.25.6-minimal-eksbuild.2",
+        Version::new(1, 25),
+      ),
+      ("localhost:5000/kube-proxy:v1.24.10@sha256:abc123", Version::new(1, 24)),
     ];
 
     for (input, expected) in input_expected {
-      let result = parse_minor(input).unwrap();
-      assert_eq!(result, expected);
+      assert_eq!(input.parse::<Version>().unwrap(), expected, "{input}");
     }
   }
 
   #[test]
-  fn can_normalize() {
-    let input_expected = vec![
-      ("v1.20.7-eks-123456", "1.20"),
-      // TODO - add more test cases and failure cases
-    ];
+  fn can_reject_invalid_version() {
+    assert_eq!(
+      "kube-proxy".parse::<Version>(),
+      Err(VersionError::Invalid("kube-proxy".to_string()))
+    );
+    assert_eq!(
+      "localhost:5000/kube-proxy".parse::<Version>(),
+      Err(VersionError::Missing("localhost:5000/kube-proxy".to_string()))
+    );
+    assert!("kube-proxy:latest".parse::<Version>().is_err());
+    assert!("".parse::<Version>().is_err());
+    assert!("v1".parse::<Version>().is_err());
+  }
 
-    for (input, expected) in input_expected {
-      let result = normalize(input).unwrap();
-      assert_eq!(result, expected);
+  #[test]
+  fn can_compare_versions() {
+    let v1_9: Version = "1.9".parse().unwrap();
+    let v1_10: Version = "v1.10.3".parse().unwrap();
+
+    assert!(v1_9 < v1_10);
+    assert_eq!(v1_9.next_minor(), Version::new(1, 10));
+    assert_eq!(v1_10.minor_skew(&v1_9), 1);
+    assert_eq!(v1_9.minor_skew(&v1_10), -1);
+    assert_eq!(v1_10.to_string(), "1.10");
+  }
+
+  #[test]
+  fn can_get_max_kubelet_skew() {
+    assert_eq!(Version::new(1, 27).max_kubelet_skew(), 2);
+    assert_eq!(Version::new(1, 28).max_kubelet_skew(), 3);
+    assert_eq!(Version::new(1, 29).max_kubelet_skew(), 3);
+  }
+
+  #[test]
+  fn can_get_support() {
+    let date = |date: &str| NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap();
+    let release = Release {
+      release_url: String::new(),
+      deprecation_url: None,
+      eks_release: Some(date("2023-02-21")),
+      end_of_standard_support: Some(date("2024-05-01")),
+      end_of_extended_support: Some(date("2025-05-01")),
+    };
+
+    assert_eq!(release.support(date("2024-04-01")), Support::Standard(30));
+    assert_eq!(release.support(date("2024-05-01")), Support::Extended(365));
+    assert_eq!(release.support(date("2025-05-11")), Support::Ended(10));
+
+    let release = Release {
+      end_of_extended_support: None,
+      ..release
+    };
+    assert_eq!(release.support(date("2024-05-02")), Support::Ended(1));
+
+    let release = Release {
+      end_of_standard_support: None,
+      ..release
+    };
+    assert_eq!(release.support(date("2024-05-02")), Support::Unknown);
+  }
+
+  #[test]
+  fn can_load_catalog() {
+    let catalog = Catalog::load(&None).unwrap();
+
+    assert!(catalog.is_supported(&Version::new(1, 26)));
+    assert!(!catalog.is_supported(&Version::new(1, 99)));
+    assert!(catalog.latest().is_some());
+
+    for release in catalog.releases.values() {
+      if let (Some(standard), Some(extended)) = (release.end_of_standard_support, release.end_of_extended_support) {
+        assert!(standard < extended);
+      }
     }
   }
+
+  #[test]
+  fn can_override_catalog() {
+    let mut catalog = Catalog::load(&None).unwrap();
+    let overrides = Catalog::from_yaml(
+      r#"
+'1.99':
+    release_url: https://kubernetes.io
+    eks_release: 2099-01-01
+'1.100':
+    release_url: https://kubernetes.io
+"#,
+    )
+    .unwrap();
+    catalog.releases.extend(overrides.releases);
+
+    assert_eq!(catalog.latest(), Some(Version::new(1, 99)));
+    assert!(!catalog.is_supported(&Version::new(1, 100)));
+    assert!(Catalog::from_yaml("'latest':\n    release_url: https://kubernetes.io\n").is_err());
+  }
 }